@@ -152,6 +152,17 @@ fn bench_lazy_vs_full_parsing(c: &mut Criterion) {
         })
     });
 
+    // Streaming path extraction - nothing but the matched names is allocated
+    let decompressed = decompress_optimized(cube_data, detect_compression(cube_data)).unwrap();
+    let palette_path = NbtPath::parse("palette[].Name").unwrap();
+    group.bench_function("stream_extract_palette_path", |b| {
+        b.iter(|| {
+            let mut reader = NbtReader::new(black_box(&decompressed), Endian::Big);
+            let palette = reader.extract_path(&palette_path).unwrap();
+            black_box(palette)
+        })
+    });
+
     group.bench_function("lazy_load_multiple", |b| {
         b.iter(|| {
             let file =
@@ -198,11 +209,89 @@ fn bench_memory_efficiency(c: &mut Criterion) {
     group.finish();
 }
 
+// Chunk-like data dominated by arrays: 24 sections of block states, heightmaps and light
+fn build_chunk_arrays() -> Vec<u8> {
+    let sections = (0..24)
+        .map(|y| {
+            let mut block_states = CompoundMap::new();
+            block_states.insert(
+                "data".to_string(),
+                NbtTag::LongArray((0..256).map(|i| i * 0x0101_0101 + y).collect()),
+            );
+            let mut section = CompoundMap::new();
+            section.insert("Y".to_string(), NbtTag::Byte(y as i8));
+            section.insert("block_states".to_string(), NbtTag::Compound(block_states));
+            section.insert("BlockLight".to_string(), NbtTag::ByteArray(vec![7; 2048]));
+            NbtTag::Compound(section)
+        })
+        .collect();
+    let mut heightmaps = CompoundMap::new();
+    for name in ["MOTION_BLOCKING", "OCEAN_FLOOR", "WORLD_SURFACE"] {
+        heightmaps.insert(name.to_string(), NbtTag::LongArray(vec![0x0102_0304_0506; 37]));
+    }
+    let mut root = CompoundMap::new();
+    root.insert("sections".to_string(), NbtTag::List { tag_type: 10, items: sections });
+    root.insert("Heightmaps".to_string(), NbtTag::Compound(heightmaps));
+    root.insert("Biomes".to_string(), NbtTag::IntArray((0..1024).collect()));
+
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer.write_tag(&NbtTag::Compound(root)).unwrap();
+    writer.into_bytes()
+}
+
+// Previous decoding strategy: one bounds-checked read per element
+fn read_long_array_per_element(reader: &mut NbtReader) -> Vec<i64> {
+    let len = reader.read_i32().unwrap() as usize;
+    let mut array = Vec::with_capacity(len);
+    for _ in 0..len {
+        array.push(reader.read_i64().unwrap());
+    }
+    array
+}
+
+fn bench_array_decoding(c: &mut Criterion) {
+    let chunk = build_chunk_arrays();
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer
+        .write_tag(&NbtTag::LongArray((0..65536).collect()))
+        .unwrap();
+    let long_array = writer.into_bytes();
+
+    let mut group = c.benchmark_group("Array Decoding");
+    group.measurement_time(Duration::from_secs(5));
+    group.sample_size(50);
+    group.warm_up_time(Duration::from_secs(2));
+
+    group.bench_function("long_array_64k_per_element", |b| {
+        b.iter(|| {
+            let mut reader = NbtReader::new(black_box(&long_array), Endian::Big);
+            black_box(read_long_array_per_element(&mut reader))
+        })
+    });
+
+    group.bench_function("long_array_64k_bulk", |b| {
+        b.iter(|| {
+            let mut reader = NbtReader::new(black_box(&long_array), Endian::Big);
+            black_box(reader.read_tag(12).unwrap())
+        })
+    });
+
+    group.bench_function("chunk_arrays_read", |b| {
+        b.iter(|| {
+            let mut reader = NbtReader::new(black_box(&chunk), Endian::Big);
+            black_box(reader.read_tag(10).unwrap())
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_optimized_loading,
     bench_streaming_operations,
     bench_lazy_vs_full_parsing,
-    bench_memory_efficiency
+    bench_memory_efficiency,
+    bench_array_decoding
 );
 criterion_main!(benches);
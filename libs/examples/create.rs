@@ -1,9 +1,9 @@
 fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     // Create large NBT: root compound with big list of compounds
-    let mut root = nbt::NbtTag::Compound(std::collections::HashMap::new());
+    let mut root = nbt::NbtTag::Compound(nbt::CompoundMap::new());
     let mut large_list = Vec::with_capacity(10000); // Aim for ~1MB
     for i in 0..10000 {
-        let mut inner_comp = std::collections::HashMap::new();
+        let mut inner_comp = nbt::CompoundMap::new();
         inner_comp.insert("id".to_string(), nbt::NbtTag::Int(i));
         inner_comp.insert(
             "name".to_string(),
@@ -1,9 +1,9 @@
 use nbt::{format_snbt, format_snbt_pretty, parse_snbt};
 use nbt::{CompressionFormat, NbtFile, NbtTag};
-use std::collections::HashMap;
+use nbt::CompoundMap;
 
 fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let mut player = HashMap::new();
+    let mut player = CompoundMap::new();
     player.insert("Name".to_string(), NbtTag::String("Steve".to_string()));
     player.insert("Level".to_string(), NbtTag::Int(30));
     player.insert("Health".to_string(), NbtTag::Double(20.0));
@@ -25,7 +25,7 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     let mut inventory = Vec::new();
     for i in 0..5 {
-        let mut item = HashMap::new();
+        let mut item = CompoundMap::new();
         item.insert(
             "id".to_string(),
             NbtTag::String(format!("minecraft:item_{}", i)),
@@ -42,7 +42,7 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         },
     );
 
-    let mut root = HashMap::new();
+    let mut root = CompoundMap::new();
     root.insert("DataVersion".to_string(), NbtTag::Int(3210));
     root.insert("Player".to_string(), NbtTag::Compound(player));
 
@@ -0,0 +1,98 @@
+use crate::convert::list_from;
+use crate::{CompoundMap, NbtTag};
+
+/// Values that convert to a single tag type, so a list built from them is always valid.
+///
+/// `NbtTag` itself is not a list element: use `NbtTag::list_of` for lists of arbitrary tags.
+pub trait ListElement: Into<NbtTag> {}
+
+macro_rules! impl_list_element {
+    ($($ty:ty),* $(,)?) => {
+        $(impl ListElement for $ty {})*
+    };
+}
+
+impl_list_element!(
+    i8,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    bool,
+    String,
+    &str,
+    Vec<i8>,
+    Vec<i32>,
+    Vec<i64>,
+    CompoundMap,
+    CompoundBuilder,
+);
+
+/// Fluent builder for compound tags.
+///
+/// ```ignore
+/// let pig = NbtTag::builder()
+///     .with("id", "minecraft:pig")
+///     .with("Health", 10.0f32)
+///     .with_list("Pos", [1.0, 64.0, 2.0])
+///     .with_compound("Owner", |owner| owner.with("Name", "Alex"))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompoundBuilder {
+    map: CompoundMap,
+}
+
+impl CompoundBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set an entry, replacing any previous value
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<NbtTag>) -> Self {
+        self.map.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set an entry only when `value` is `Some`
+    pub fn with_optional(self, key: impl Into<String>, value: Option<impl Into<NbtTag>>) -> Self {
+        match value {
+            Some(value) => self.with(key, value),
+            None => self,
+        }
+    }
+
+    /// Set a nested compound built by `build`
+    pub fn with_compound(
+        self,
+        key: impl Into<String>,
+        build: impl FnOnce(CompoundBuilder) -> CompoundBuilder,
+    ) -> Self {
+        let nested = build(CompoundBuilder::new());
+        self.with(key, nested)
+    }
+
+    /// Set a list; all elements share one tag type by construction
+    pub fn with_list<T: ListElement>(
+        self,
+        key: impl Into<String>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.with(key, list_from(items))
+    }
+
+    pub fn build(self) -> NbtTag {
+        NbtTag::Compound(self.map)
+    }
+
+    pub fn into_map(self) -> CompoundMap {
+        self.map
+    }
+}
+
+impl From<CompoundBuilder> for NbtTag {
+    fn from(builder: CompoundBuilder) -> Self {
+        builder.build()
+    }
+}
@@ -0,0 +1,262 @@
+use crate::{CompoundMap, NbtTag};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+const FNV64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+const FNV128_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV128_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+impl NbtTag {
+    // Canonical encoding: compound keys sorted, NaN normalised, empty lists typed End.
+    // Equal bytes means equal content, whatever the key order of the compounds
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(self, &mut out);
+        out
+    }
+
+    // Stable 64-bit FNV-1a hash of the canonical bytes (the same across runs and platforms)
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv64(FNV64_OFFSET);
+        encode(self, &mut hasher);
+        hasher.0
+    }
+
+    // Stable 128-bit FNV-1a hash of the canonical bytes
+    pub fn fingerprint128(&self) -> u128 {
+        let mut hasher = Fnv128(FNV128_OFFSET);
+        encode(self, &mut hasher);
+        hasher.0
+    }
+
+    // Total order consistent with `canonical_bytes` equality
+    pub fn canonical_cmp(&self, other: &NbtTag) -> Ordering {
+        compare(self, other)
+    }
+}
+
+/// `NbtTag` with `Eq`, `Ord` and `Hash` on its canonical form, for map keys and deduplication
+///
+/// Compounds compare equal regardless of key order. Floats compare by bits, with every NaN
+/// equal to every other NaN and `0.0` distinct from `-0.0`; empty lists are equal whatever
+/// their element type. Tags are ordered by type ID first, then by content.
+#[derive(Debug, Clone)]
+pub struct CanonicalTag(pub NbtTag);
+
+impl CanonicalTag {
+    pub fn new(tag: NbtTag) -> Self {
+        Self(tag)
+    }
+
+    pub fn into_inner(self) -> NbtTag {
+        self.0
+    }
+}
+
+impl From<NbtTag> for CanonicalTag {
+    fn from(tag: NbtTag) -> Self {
+        Self(tag)
+    }
+}
+
+impl Deref for CanonicalTag {
+    type Target = NbtTag;
+
+    fn deref(&self) -> &NbtTag {
+        &self.0
+    }
+}
+
+impl PartialEq for CanonicalTag {
+    fn eq(&self, other: &Self) -> bool {
+        compare(&self.0, &other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for CanonicalTag {}
+
+impl PartialOrd for CanonicalTag {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalTag {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(&self.0, &other.0)
+    }
+}
+
+impl Hash for CanonicalTag {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        encode(&self.0, &mut HasherSink(state));
+    }
+}
+
+trait Sink {
+    fn put(&mut self, bytes: &[u8]);
+}
+
+impl Sink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+struct HasherSink<'a, H>(&'a mut H);
+
+impl<H: Hasher> Sink for HasherSink<'_, H> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+struct Fnv64(u64);
+
+impl Sink for Fnv64 {
+    fn put(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV64_PRIME);
+        }
+    }
+}
+
+struct Fnv128(u128);
+
+impl Sink for Fnv128 {
+    fn put(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u128).wrapping_mul(FNV128_PRIME);
+        }
+    }
+}
+
+fn encode(tag: &NbtTag, out: &mut impl Sink) {
+    out.put(&[tag.type_id()]);
+    encode_payload(tag, out);
+}
+
+// Big-endian payloads with u32 lengths; list elements carry no type byte
+fn encode_payload(tag: &NbtTag, out: &mut impl Sink) {
+    match tag {
+        NbtTag::End => {}
+        NbtTag::Byte(v) => out.put(&v.to_be_bytes()),
+        NbtTag::Short(v) => out.put(&v.to_be_bytes()),
+        NbtTag::Int(v) => out.put(&v.to_be_bytes()),
+        NbtTag::Long(v) => out.put(&v.to_be_bytes()),
+        NbtTag::Float(v) => out.put(&float_bits(*v).to_be_bytes()),
+        NbtTag::Double(v) => out.put(&double_bits(*v).to_be_bytes()),
+        NbtTag::ByteArray(values) => {
+            encode_len(values.len(), out);
+            for v in values {
+                out.put(&v.to_be_bytes());
+            }
+        }
+        NbtTag::String(value) => encode_str(value, out),
+        NbtTag::List { items, .. } => {
+            out.put(&[list_type(tag)]);
+            encode_len(items.len(), out);
+            for item in items {
+                encode_payload(item, out);
+            }
+        }
+        NbtTag::Compound(map) => {
+            let entries = sorted_entries(map);
+            encode_len(entries.len(), out);
+            for (key, value) in entries {
+                encode_str(key, out);
+                encode(value, out);
+            }
+        }
+        NbtTag::IntArray(values) => {
+            encode_len(values.len(), out);
+            for v in values {
+                out.put(&v.to_be_bytes());
+            }
+        }
+        NbtTag::LongArray(values) => {
+            encode_len(values.len(), out);
+            for v in values {
+                out.put(&v.to_be_bytes());
+            }
+        }
+    }
+}
+
+fn encode_len(len: usize, out: &mut impl Sink) {
+    out.put(&(len as u32).to_be_bytes());
+}
+
+fn encode_str(value: &str, out: &mut impl Sink) {
+    encode_len(value.len(), out);
+    out.put(value.as_bytes());
+}
+
+fn compare(a: &NbtTag, b: &NbtTag) -> Ordering {
+    match (a, b) {
+        (NbtTag::Byte(x), NbtTag::Byte(y)) => x.cmp(y),
+        (NbtTag::Short(x), NbtTag::Short(y)) => x.cmp(y),
+        (NbtTag::Int(x), NbtTag::Int(y)) => x.cmp(y),
+        (NbtTag::Long(x), NbtTag::Long(y)) => x.cmp(y),
+        (NbtTag::Float(x), NbtTag::Float(y)) => {
+            f32::from_bits(float_bits(*x)).total_cmp(&f32::from_bits(float_bits(*y)))
+        }
+        (NbtTag::Double(x), NbtTag::Double(y)) => {
+            f64::from_bits(double_bits(*x)).total_cmp(&f64::from_bits(double_bits(*y)))
+        }
+        (NbtTag::ByteArray(x), NbtTag::ByteArray(y)) => x.cmp(y),
+        (NbtTag::String(x), NbtTag::String(y)) => x.cmp(y),
+        (NbtTag::List { items: x, .. }, NbtTag::List { items: y, .. }) => {
+            list_type(a).cmp(&list_type(b)).then_with(|| {
+                x.iter()
+                    .zip(y)
+                    .map(|(x, y)| compare(x, y))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| x.len().cmp(&y.len()))
+            })
+        }
+        (NbtTag::Compound(x), NbtTag::Compound(y)) => {
+            let (x, y) = (sorted_entries(x), sorted_entries(y));
+            x.iter()
+                .zip(&y)
+                .map(|((kx, vx), (ky, vy))| kx.cmp(ky).then_with(|| compare(vx, vy)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| x.len().cmp(&y.len()))
+        }
+        (NbtTag::IntArray(x), NbtTag::IntArray(y)) => x.cmp(y),
+        (NbtTag::LongArray(x), NbtTag::LongArray(y)) => x.cmp(y),
+        _ => a.type_id().cmp(&b.type_id()),
+    }
+}
+
+fn sorted_entries(map: &CompoundMap) -> Vec<(&String, &NbtTag)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn list_type(tag: &NbtTag) -> u8 {
+    match tag {
+        NbtTag::List { items, .. } if items.is_empty() => 0,
+        NbtTag::List { tag_type, .. } => *tag_type,
+        _ => 0,
+    }
+}
+
+fn float_bits(v: f32) -> u32 {
+    if v.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        v.to_bits()
+    }
+}
+
+fn double_bits(v: f64) -> u64 {
+    if v.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        v.to_bits()
+    }
+}
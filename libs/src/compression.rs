@@ -1,9 +1,12 @@
-use crate::{Endian, NbtError, NbtReader, NbtTag, NbtWriter, Result};
+use crate::{Endian, NbtError, NbtLimits, NbtReader, NbtStreamWriter, NbtTag, Result};
 
 use flate2::read::{GzDecoder, ZlibDecoder};
 use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::Compression;
-use std::io::{Read, Write};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CompressionFormat {
@@ -60,6 +63,34 @@ pub fn decompress_optimized(data: &[u8], format: CompressionFormat) -> Result<Ve
     }
 }
 
+/// Decompress, failing once the output exceeds `max_size` bytes (guards against zip bombs)
+pub fn decompress_limited(
+    data: &[u8],
+    format: CompressionFormat,
+    max_size: usize,
+) -> Result<Vec<u8>> {
+    match format {
+        CompressionFormat::None => read_limited(data, max_size, format),
+        CompressionFormat::Gzip => read_limited(GzDecoder::new(data), max_size, format),
+        CompressionFormat::Zlib => read_limited(ZlibDecoder::new(data), max_size, format),
+    }
+}
+
+fn read_limited(reader: impl Read, max_size: usize, format: CompressionFormat) -> Result<Vec<u8>> {
+    let mut result = Vec::new();
+    reader
+        .take((max_size as u64).saturating_add(1))
+        .read_to_end(&mut result)
+        .map_err(|e| match format {
+            CompressionFormat::None => NbtError::Io(e),
+            _ => NbtError::compression_error(format!("Decompression failed: {e}")),
+        })?;
+    if result.len() > max_size {
+        return Err(NbtError::DecompressedSizeExceeded { limit: max_size });
+    }
+    Ok(result)
+}
+
 pub fn compress_data(data: &[u8], format: CompressionFormat) -> Result<Vec<u8>> {
     match format {
         CompressionFormat::None => Ok(data.to_vec()),
@@ -84,7 +115,55 @@ pub fn compress_data(data: &[u8], format: CompressionFormat) -> Result<Vec<u8>>
     }
 }
 
-#[derive(Debug)]
+/// `io::Write` adapter that compresses with the given format, for `NbtStreamWriter`
+pub enum CompressedWriter<W: Write> {
+    None(W),
+    Gzip(GzEncoder<W>),
+    Zlib(ZlibEncoder<W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    pub fn new(inner: W, format: CompressionFormat) -> Self {
+        match format {
+            CompressionFormat::None => Self::None(inner),
+            CompressionFormat::Gzip => Self::Gzip(GzEncoder::new(inner, Compression::default())),
+            CompressionFormat::Zlib => Self::Zlib(ZlibEncoder::new(inner, Compression::default())),
+        }
+    }
+
+    /// Write the compression trailer and return the inner writer
+    pub fn finish(self) -> Result<W> {
+        match self {
+            Self::None(inner) => Ok(inner),
+            Self::Gzip(encoder) => encoder
+                .finish()
+                .map_err(|e| NbtError::compression_error(format!("Gzip finish failed: {e}"))),
+            Self::Zlib(encoder) => encoder
+                .finish()
+                .map_err(|e| NbtError::compression_error(format!("Zlib finish failed: {e}"))),
+        }
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::None(inner) => inner.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+            Self::Zlib(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::None(inner) => inner.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+            Self::Zlib(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct NbtFile {
     pub root: NbtTag,
     pub root_name: String,
@@ -93,31 +172,19 @@ pub struct NbtFile {
 
 impl NbtFile {
     pub fn read(data: &[u8], fields: Option<&[&str]>) -> Result<Self> {
-        let fields = fields.unwrap_or(&[]);
-        let format = detect_compression(data);
-
-        let decompressed = decompress_optimized(data, format)?;
-
-        let mut reader = NbtReader::new(&decompressed, Endian::Big);
-        let tag_type = reader.read_u8()?;
-
-        if tag_type != 10 {
-            return Err(NbtError::Parse("Expected compound tag at root".to_string()));
-        }
-
-        let root_name = reader.read_string()?;
+        Self::read_with_format(data, detect_compression(data), Endian::Big, fields)
+    }
 
-        let root = if fields.is_empty() {
-            reader.read_tag(tag_type)?
-        } else {
-            reader.read_compound_selective(fields)?
+    /// Read untrusted data, enforcing the decompressed size, nesting and allocation limits
+    pub fn read_with_limits(data: &[u8], limits: NbtLimits) -> Result<Self> {
+        let format = detect_compression(data);
+        let decompressed = match format {
+            CompressionFormat::None if data.len() <= limits.max_decompressed_size => {
+                Cow::Borrowed(data)
+            }
+            _ => Cow::Owned(decompress_limited(data, format, limits.max_decompressed_size)?),
         };
-
-        Ok(Self {
-            root,
-            root_name,
-            compression: format,
-        })
+        Self::parse(&decompressed, format, Endian::Big, None, limits)
     }
 
     pub fn new_with_settings(
@@ -143,10 +210,78 @@ impl NbtFile {
         endian: Endian,
         fields: Option<&[&str]>,
     ) -> Result<Self> {
-        let fields = fields.unwrap_or(&[]);
-        let decompressed = decompress_optimized(data, format)?;
+        // Uncompressed data is parsed in place
+        let decompressed = match format {
+            CompressionFormat::None => Cow::Borrowed(data),
+            _ => Cow::Owned(decompress_optimized(data, format)?),
+        };
+        Self::parse(&decompressed, format, endian, fields, NbtLimits::unlimited())
+    }
+
+    /// Read a file from any reader, decompressing while reading (format detected from the magic bytes)
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with_limits(reader, NbtLimits::unlimited())
+    }
+
+    /// Like `from_reader`, stopping as soon as the decompressed data exceeds the limits
+    pub fn from_reader_with_limits<R: Read>(mut reader: R, limits: NbtLimits) -> Result<Self> {
+        let mut magic = [0u8; 2];
+        let mut peeked = 0;
+        while peeked < magic.len() {
+            match reader.read(&mut magic[peeked..])? {
+                0 => break,
+                n => peeked += n,
+            }
+        }
+        let format = detect_compression(&magic[..peeked]);
+        let stream = (&magic[..peeked]).chain(reader);
+
+        let max_size = limits.max_decompressed_size;
+        let data = match format {
+            CompressionFormat::None => read_limited(stream, max_size, format),
+            CompressionFormat::Gzip => read_limited(GzDecoder::new(stream), max_size, format),
+            CompressionFormat::Zlib => read_limited(ZlibDecoder::new(stream), max_size, format),
+        }?;
+
+        Self::parse(&data, format, Endian::Big, None, limits)
+    }
 
-        let mut reader = NbtReader::new(&decompressed, endian);
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Write the file to any writer, compressing while writing
+    ///
+    /// The tree is streamed through the compressor, the payload is never fully buffered.
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        let output = BufWriter::new(CompressedWriter::new(writer, self.compression));
+        let mut nbt = NbtStreamWriter::new(output, Endian::Big);
+        nbt.key(&self.root_name)?;
+        nbt.write_tag(&self.root)?;
+        let output = nbt.finish()?.into_inner().map_err(|e| e.into_error())?;
+        output.finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.to_writer(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn write(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.to_writer(&mut data)?;
+        Ok(data)
+    }
+
+    fn parse(
+        data: &[u8],
+        format: CompressionFormat,
+        endian: Endian,
+        fields: Option<&[&str]>,
+        limits: NbtLimits,
+    ) -> Result<Self> {
+        let fields = fields.unwrap_or(&[]);
+        let mut reader = NbtReader::with_limits(data, endian, limits);
         let tag_type = reader.read_u8()?;
 
         if tag_type != 10 {
@@ -154,7 +289,7 @@ impl NbtFile {
         }
 
         let root_name = reader.read_string()?;
-        
+
         let root = if fields.is_empty() {
             reader.read_tag(tag_type)?
         } else {
@@ -168,16 +303,6 @@ impl NbtFile {
         })
     }
 
-    pub fn write(&self) -> Result<Vec<u8>> {
-        let mut writer = NbtWriter::new(Endian::Big);
-        writer.write_u8(10); // Compound tag
-        writer.write_string(&self.root_name);
-        writer.write_tag(&self.root)?;
-
-        let uncompressed = writer.into_bytes();
-        compress_data(&uncompressed, self.compression)
-    }
-
     pub fn get(&self, key: &str) -> Option<&NbtTag> {
         self.root.get(key)
     }
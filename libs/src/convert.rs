@@ -0,0 +1,310 @@
+use crate::{CompoundMap, NbtError, NbtTag, Result};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// Conversion from an `NbtTag`, failing with a path-aware `TypeMismatch`
+pub trait FromNbt: Sized {
+    fn from_nbt(tag: &NbtTag) -> Result<Self>;
+}
+
+/// Conversion into an `NbtTag`
+pub trait ToNbt {
+    fn to_nbt(&self) -> NbtTag;
+}
+
+/// Typed view over the entries of a compound, usually implemented with `#[derive(NbtCompound)]`
+pub trait NbtCompound: Sized {
+    fn from_compound(map: &CompoundMap) -> Result<Self>;
+
+    fn write_compound(&self, map: &mut CompoundMap);
+
+    fn to_compound(&self) -> CompoundMap {
+        let mut map = CompoundMap::new();
+        self.write_compound(&mut map);
+        map
+    }
+}
+
+/// Read a compound tag into `T`
+pub fn compound_from_nbt<T: NbtCompound>(tag: &NbtTag) -> Result<T> {
+    match tag {
+        NbtTag::Compound(map) => T::from_compound(map),
+        other => Err(NbtError::type_mismatch("Compound", other.type_name())),
+    }
+}
+
+/// Read a required field, prefixing errors with its key
+pub fn read_field<T: FromNbt>(map: &CompoundMap, key: &str) -> Result<T> {
+    match map.get(key) {
+        Some(tag) => T::from_nbt(tag).map_err(|e| e.at_key(key)),
+        None => Err(NbtError::MissingKey {
+            path: String::new(),
+        }
+        .at_key(key)),
+    }
+}
+
+/// Read an optional field (`None` if the key is missing)
+pub fn read_optional_field<T: FromNbt>(map: &CompoundMap, key: &str) -> Result<Option<T>> {
+    map.get(key)
+        .map(|tag| T::from_nbt(tag).map_err(|e| e.at_key(key)))
+        .transpose()
+}
+
+fn mismatch<T>(expected: &str, tag: &NbtTag) -> Result<T> {
+    Err(NbtError::type_mismatch(expected, tag.type_name()))
+}
+
+macro_rules! impl_primitive {
+    ($ty:ty, $variant:ident) => {
+        impl FromNbt for $ty {
+            fn from_nbt(tag: &NbtTag) -> Result<Self> {
+                match tag {
+                    NbtTag::$variant(v) => Ok(*v),
+                    other => mismatch(stringify!($variant), other),
+                }
+            }
+        }
+
+        impl ToNbt for $ty {
+            fn to_nbt(&self) -> NbtTag {
+                NbtTag::$variant(*self)
+            }
+        }
+    };
+}
+
+impl_primitive!(i8, Byte);
+impl_primitive!(i16, Short);
+impl_primitive!(i32, Int);
+impl_primitive!(i64, Long);
+impl_primitive!(f32, Float);
+impl_primitive!(f64, Double);
+
+impl FromNbt for bool {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        match tag {
+            NbtTag::Byte(v) => Ok(*v != 0),
+            other => mismatch("Byte", other),
+        }
+    }
+}
+
+impl ToNbt for bool {
+    fn to_nbt(&self) -> NbtTag {
+        NbtTag::Byte(*self as i8)
+    }
+}
+
+impl FromNbt for String {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        match tag {
+            NbtTag::String(s) => Ok(s.clone()),
+            other => mismatch("String", other),
+        }
+    }
+}
+
+impl ToNbt for String {
+    fn to_nbt(&self) -> NbtTag {
+        NbtTag::String(self.clone())
+    }
+}
+
+impl ToNbt for str {
+    fn to_nbt(&self) -> NbtTag {
+        NbtTag::String(self.to_string())
+    }
+}
+
+impl FromNbt for NbtTag {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        Ok(tag.clone())
+    }
+}
+
+impl ToNbt for NbtTag {
+    fn to_nbt(&self) -> NbtTag {
+        self.clone()
+    }
+}
+
+// Lists, plus typed arrays whose elements convert to T
+impl<T: FromNbt> FromNbt for Vec<T> {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        fn convert<T: FromNbt>(items: impl Iterator<Item = NbtTag>) -> Result<Vec<T>> {
+            items
+                .enumerate()
+                .map(|(i, item)| T::from_nbt(&item).map_err(|e| e.at_index(i)))
+                .collect()
+        }
+
+        match tag {
+            NbtTag::List { items, .. } => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| T::from_nbt(item).map_err(|e| e.at_index(i)))
+                .collect(),
+            NbtTag::ByteArray(values) => convert(values.iter().map(|v| NbtTag::Byte(*v))),
+            NbtTag::IntArray(values) => convert(values.iter().map(|v| NbtTag::Int(*v))),
+            NbtTag::LongArray(values) => convert(values.iter().map(|v| NbtTag::Long(*v))),
+            other => mismatch("List", other),
+        }
+    }
+}
+
+// Element type comes from the first item (End for empty lists)
+impl<T: ToNbt> ToNbt for Vec<T> {
+    fn to_nbt(&self) -> NbtTag {
+        let items: Vec<NbtTag> = self.iter().map(ToNbt::to_nbt).collect();
+        NbtTag::List {
+            tag_type: items.first().map_or(0, NbtTag::type_id),
+            items,
+        }
+    }
+}
+
+impl<T: FromNbt> FromNbt for Option<T> {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        T::from_nbt(tag).map(Some)
+    }
+}
+
+fn map_from_compound<T: FromNbt, M: FromIterator<(String, T)>>(map: &CompoundMap) -> Result<M> {
+    map.iter()
+        .map(|(key, value)| {
+            T::from_nbt(value)
+                .map(|v| (key.clone(), v))
+                .map_err(|e| e.at_key(key))
+        })
+        .collect()
+}
+
+macro_rules! impl_map {
+    ($map:ident) => {
+        impl<T: FromNbt> FromNbt for $map<String, T> {
+            fn from_nbt(tag: &NbtTag) -> Result<Self> {
+                match tag {
+                    NbtTag::Compound(map) => map_from_compound(map),
+                    other => mismatch("Compound", other),
+                }
+            }
+        }
+
+        impl<T: ToNbt> ToNbt for $map<String, T> {
+            fn to_nbt(&self) -> NbtTag {
+                NbtTag::Compound(
+                    self.iter()
+                        .map(|(key, value)| (key.clone(), value.to_nbt()))
+                        .collect(),
+                )
+            }
+        }
+
+        // Flattening a map captures every entry of the surrounding compound
+        impl<T: FromNbt + ToNbt> NbtCompound for $map<String, T> {
+            fn from_compound(map: &CompoundMap) -> Result<Self> {
+                map_from_compound(map)
+            }
+
+            fn write_compound(&self, map: &mut CompoundMap) {
+                for (key, value) in self {
+                    map.insert(key.clone(), value.to_nbt());
+                }
+            }
+        }
+    };
+}
+
+impl_map!(HashMap);
+impl_map!(IndexMap);
+
+impl<T: ToNbt + ?Sized> ToNbt for &T {
+    fn to_nbt(&self) -> NbtTag {
+        (**self).to_nbt()
+    }
+}
+
+// Plain `From` conversions; integer `Vec`s become typed arrays as in vanilla
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for NbtTag {
+                fn from(value: $ty) -> Self {
+                    NbtTag::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    Vec<i8> => ByteArray,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray,
+);
+
+impl From<bool> for NbtTag {
+    fn from(value: bool) -> Self {
+        NbtTag::Byte(value as i8)
+    }
+}
+
+impl From<&str> for NbtTag {
+    fn from(value: &str) -> Self {
+        NbtTag::String(value.to_string())
+    }
+}
+
+impl From<&String> for NbtTag {
+    fn from(value: &String) -> Self {
+        NbtTag::String(value.clone())
+    }
+}
+
+// Other `Vec`s become lists; their element type always maps to one tag type
+macro_rules! impl_from_vec {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl From<Vec<$ty>> for NbtTag {
+                fn from(values: Vec<$ty>) -> Self {
+                    list_from(values)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_vec!(i16, f32, f64, bool, String, &str, CompoundMap);
+
+pub(crate) fn list_from<T: Into<NbtTag>>(values: impl IntoIterator<Item = T>) -> NbtTag {
+    let items: Vec<NbtTag> = values.into_iter().map(Into::into).collect();
+    NbtTag::List {
+        tag_type: items.first().map_or(0, NbtTag::type_id),
+        items,
+    }
+}
+
+macro_rules! impl_from_map {
+    ($map:ident) => {
+        impl<T: Into<NbtTag>> From<$map<String, T>> for NbtTag {
+            fn from(map: $map<String, T>) -> Self {
+                NbtTag::Compound(
+                    map.into_iter()
+                        .map(|(key, value)| (key, value.into()))
+                        .collect(),
+                )
+            }
+        }
+    };
+}
+
+impl_from_map!(HashMap);
+impl_from_map!(IndexMap);
@@ -0,0 +1,777 @@
+use crate::ser::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN, TAG_TOKEN};
+use crate::{
+    decompress_optimized, detect_compression, tag_type_name, CompoundMap, Endian, NbtError, NbtReader,
+    NbtTag, Result,
+};
+use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+impl de::Error for NbtError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NbtError::Serde(msg.to_string())
+    }
+}
+
+/// Deserialize a value from an NBT tag, borrowing strings from it
+pub fn from_tag<'de, T: Deserialize<'de>>(tag: &'de NbtTag) -> Result<T> {
+    T::deserialize(TagDeserializer::new(tag))
+}
+
+/// Deserialize a value from uncompressed big-endian NBT bytes
+///
+/// Strings are borrowed from `data` when the target type allows it.
+pub fn from_bytes<'de, T: Deserialize<'de>>(data: &'de [u8]) -> Result<T> {
+    let mut reader = NbtReader::new(data, Endian::Big);
+    from_nbt_reader(&mut reader)
+}
+
+/// Deserialize a value from gzip, zlib or uncompressed NBT bytes
+pub fn from_compressed_bytes<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    let decompressed = decompress_optimized(data, detect_compression(data))?;
+    from_bytes(&decompressed)
+}
+
+/// Deserialize the named root tag at the reader position
+pub fn from_nbt_reader<'de, T: Deserialize<'de>>(reader: &mut NbtReader<'de>) -> Result<T> {
+    let tag_type = reader.read_u8()?;
+    let _root_name = reader.read_str()?;
+    T::deserialize(BinaryDeserializer::new(reader, tag_type))
+}
+
+/// Serde deserializer reading from an `NbtTag`
+pub struct TagDeserializer<'de> {
+    tag: &'de NbtTag,
+}
+
+impl<'de> TagDeserializer<'de> {
+    pub fn new(tag: &'de NbtTag) -> Self {
+        Self { tag }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for TagDeserializer<'de> {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::End => visitor.visit_unit(),
+            NbtTag::Byte(v) => visitor.visit_i8(*v),
+            NbtTag::Short(v) => visitor.visit_i16(*v),
+            NbtTag::Int(v) => visitor.visit_i32(*v),
+            NbtTag::Long(v) => visitor.visit_i64(*v),
+            NbtTag::Float(v) => visitor.visit_f32(*v),
+            NbtTag::Double(v) => visitor.visit_f64(*v),
+            NbtTag::String(s) => visitor.visit_borrowed_str(s),
+            NbtTag::ByteArray(v) => visit_array(v.iter().map(|&v| ArrayElement::Byte(v)), visitor),
+            NbtTag::IntArray(v) => visit_array(v.iter().map(|&v| ArrayElement::Int(v)), visitor),
+            NbtTag::LongArray(v) => visit_array(v.iter().map(|&v| ArrayElement::Long(v)), visitor),
+            NbtTag::List { items, .. } => visitor.visit_seq(TagSeqAccess { iter: items.iter() }),
+            NbtTag::Compound(map) => visitor.visit_map(TagMapAccess {
+                iter: map.iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::Byte(v) => visitor.visit_bool(*v != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::Byte(v) => visitor.visit_u8(*v as u8),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::Short(v) => visitor.visit_u16(*v as u16),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::Int(v) => visitor.visit_u32(*v as u32),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::Long(v) => visitor.visit_u64(*v as u64),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::ByteArray(v) => visitor.visit_byte_buf(v.iter().map(|&b| b as u8).collect()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::End => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == TAG_TOKEN {
+            let token = match self.tag {
+                NbtTag::ByteArray(_) => BYTE_ARRAY_TOKEN,
+                NbtTag::IntArray(_) => INT_ARRAY_TOKEN,
+                NbtTag::LongArray(_) => LONG_ARRAY_TOKEN,
+                _ => return self.deserialize_any(visitor),
+            };
+            return visitor.visit_map(ArrayMapAccess {
+                token: Some(token),
+                value: Some(self),
+            });
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.tag {
+            NbtTag::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            NbtTag::Compound(map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().unwrap();
+                visitor.visit_enum(TagEnumAccess { variant, value })
+            }
+            tag => Err(NbtError::Serde(format!(
+                "Expected String or single-entry Compound for enum, found {}",
+                tag.type_name()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string unit unit_struct seq
+        tuple tuple_struct map struct identifier
+    }
+}
+
+fn visit_array<'de, I, V>(iter: I, visitor: V) -> Result<V::Value>
+where
+    I: Iterator<Item = ArrayElement>,
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(iter);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+// Typed array element; unsigned targets reinterpret the bits like `BinaryDeserializer` does,
+// so `Vec<u8>` / `Vec<u32>` / `Vec<u64>` round-trip through the array helpers
+#[derive(Clone, Copy)]
+enum ArrayElement {
+    Byte(i8),
+    Int(i32),
+    Long(i64),
+}
+
+impl<'de> IntoDeserializer<'de, NbtError> for ArrayElement {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ArrayElement {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            ArrayElement::Byte(v) => visitor.visit_i8(v),
+            ArrayElement::Int(v) => visitor.visit_i32(v),
+            ArrayElement::Long(v) => visitor.visit_i64(v),
+        }
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            ArrayElement::Byte(v) => visitor.visit_u8(v as u8),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            ArrayElement::Int(v) => visitor.visit_u32(v as u32),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            ArrayElement::Long(v) => visitor.visit_u64(v as u64),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u16 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+struct TagSeqAccess<'de> {
+    iter: std::slice::Iter<'de, NbtTag>,
+}
+
+impl<'de> SeqAccess<'de> for TagSeqAccess<'de> {
+    type Error = NbtError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.iter
+            .next()
+            .map(|tag| seed.deserialize(TagDeserializer::new(tag)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct TagMapAccess<'de, I> {
+    iter: I,
+    value: Option<&'de NbtTag>,
+}
+
+impl<'de, I> MapAccess<'de> for TagMapAccess<'de, I>
+where
+    I: Iterator<Item = (&'de String, &'de NbtTag)>,
+{
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(key.as_str()))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| NbtError::Serde("Value requested before key".to_string()))?;
+        seed.deserialize(TagDeserializer::new(value))
+    }
+}
+
+// Presents a typed array as `{token: [values]}` so `NbtTag` keeps the array type
+struct ArrayMapAccess<D> {
+    token: Option<&'static str>,
+    value: Option<D>,
+}
+
+impl<'de, D> MapAccess<'de> for ArrayMapAccess<D>
+where
+    D: de::Deserializer<'de, Error = NbtError>,
+{
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        self.token
+            .take()
+            .map(|token| seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(token)))
+            .transpose()
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| NbtError::Serde("Value requested before key".to_string()))?;
+        seed.deserialize(value)
+    }
+}
+
+struct TagEnumAccess<'de> {
+    variant: &'de str,
+    value: &'de NbtTag,
+}
+
+impl<'de> EnumAccess<'de> for TagEnumAccess<'de> {
+    type Error = NbtError;
+    type Variant = TagDeserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(self.variant))?;
+        Ok((variant, TagDeserializer::new(self.value)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for TagDeserializer<'de> {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// Serde deserializer reading binary NBT straight from an `NbtReader`
+///
+/// No intermediate `NbtTag` is built; strings and byte arrays are
+/// borrowed from the input, and ignored fields are skipped.
+pub struct BinaryDeserializer<'r, 'de> {
+    reader: &'r mut NbtReader<'de>,
+    tag_type: u8,
+}
+
+impl<'r, 'de> BinaryDeserializer<'r, 'de> {
+    /// Deserialize the payload of a tag of `tag_type` at the reader position
+    pub fn new(reader: &'r mut NbtReader<'de>, tag_type: u8) -> Self {
+        Self { reader, tag_type }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for BinaryDeserializer<'_, 'de> {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            0 => visitor.visit_unit(),
+            1 => visitor.visit_i8(self.reader.read_i8()?),
+            2 => visitor.visit_i16(self.reader.read_i16()?),
+            3 => visitor.visit_i32(self.reader.read_i32()?),
+            4 => visitor.visit_i64(self.reader.read_i64()?),
+            5 => visitor.visit_f32(self.reader.read_f32()?),
+            6 => visitor.visit_f64(self.reader.read_f64()?),
+            8 => visitor.visit_borrowed_str(self.reader.read_str()?),
+            7 | 11 | 12 => {
+                let element_type = match self.tag_type {
+                    7 => 1,
+                    11 => 3,
+                    _ => 4,
+                };
+                let len = self.reader.read_len()?;
+                visit_binary_seq(self.reader, element_type, len, visitor)
+            }
+            9 => {
+                let element_type = self.reader.read_u8()?;
+                let len = self.reader.read_len()?;
+                self.reader.enter()?;
+                let value = visit_binary_seq(self.reader, element_type, len, visitor);
+                self.reader.leave();
+                value
+            }
+            10 => {
+                self.reader.enter()?;
+                let value = visitor.visit_map(BinaryMapAccess {
+                    reader: &mut *self.reader,
+                    value_type: 0,
+                });
+                self.reader.leave();
+                value
+            }
+            tag_type => Err(NbtError::InvalidTagType(tag_type)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            1 => visitor.visit_bool(self.reader.read_i8()? != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            1 => visitor.visit_u8(self.reader.read_u8()?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            2 => visitor.visit_u16(self.reader.read_i16()? as u16),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            3 => visitor.visit_u32(self.reader.read_i32()? as u32),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            4 => visitor.visit_u64(self.reader.read_i64()? as u64),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            7 => {
+                let len = self.reader.read_len()?;
+                visitor.visit_borrowed_bytes(self.reader.read_bytes(len)?)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag_type {
+            0 => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == TAG_TOKEN {
+            let token = match self.tag_type {
+                7 => BYTE_ARRAY_TOKEN,
+                11 => INT_ARRAY_TOKEN,
+                12 => LONG_ARRAY_TOKEN,
+                _ => return self.deserialize_any(visitor),
+            };
+            return visitor.visit_map(ArrayMapAccess {
+                token: Some(token),
+                value: Some(self),
+            });
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.tag_type {
+            8 => visitor.visit_enum(self.reader.read_str()?.into_deserializer()),
+            10 => {
+                let value_type = self.reader.read_u8()?;
+                if value_type == 0 {
+                    return Err(NbtError::Serde(
+                        "Expected single-entry Compound for enum, found empty Compound".to_string(),
+                    ));
+                }
+                let variant = self.reader.read_str()?;
+                let value = visitor.visit_enum(BinaryEnumAccess {
+                    reader: &mut *self.reader,
+                    variant,
+                    value_type,
+                })?;
+                match self.reader.read_u8()? {
+                    0 => Ok(value),
+                    _ => Err(NbtError::Serde(
+                        "Expected single-entry Compound for enum".to_string(),
+                    )),
+                }
+            }
+            tag_type => Err(NbtError::Serde(format!(
+                "Expected String or single-entry Compound for enum, found {}",
+                tag_type_name(tag_type)
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.reader.skip_tag(self.tag_type)?;
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string unit unit_struct seq
+        tuple tuple_struct map struct identifier
+    }
+}
+
+// Fails like `SeqDeserializer::end` when the visitor leaves elements unread, which would
+// otherwise stay in the byte stream and desynchronise the reader
+fn visit_binary_seq<'de, V: Visitor<'de>>(
+    reader: &mut NbtReader<'de>,
+    element_type: u8,
+    len: usize,
+    visitor: V,
+) -> Result<V::Value> {
+    let mut seq = BinarySeqAccess {
+        reader,
+        element_type,
+        remaining: len,
+    };
+    let value = visitor.visit_seq(&mut seq)?;
+    if seq.remaining > 0 {
+        let read = format!("{} elements in sequence", len - seq.remaining);
+        return Err(de::Error::invalid_length(len, &read.as_str()));
+    }
+    Ok(value)
+}
+
+struct BinarySeqAccess<'r, 'de> {
+    reader: &'r mut NbtReader<'de>,
+    element_type: u8,
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for BinarySeqAccess<'_, 'de> {
+    type Error = NbtError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(BinaryDeserializer::new(self.reader, self.element_type))
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct BinaryMapAccess<'r, 'de> {
+    reader: &'r mut NbtReader<'de>,
+    value_type: u8,
+}
+
+impl<'de> MapAccess<'de> for BinaryMapAccess<'_, 'de> {
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        self.value_type = self.reader.read_u8()?;
+        if self.value_type == 0 {
+            return Ok(None);
+        }
+        let key = self.reader.read_str()?;
+        seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(BinaryDeserializer::new(self.reader, self.value_type))
+    }
+}
+
+struct BinaryEnumAccess<'r, 'de> {
+    reader: &'r mut NbtReader<'de>,
+    variant: &'de str,
+    value_type: u8,
+}
+
+impl<'r, 'de> EnumAccess<'de> for BinaryEnumAccess<'r, 'de> {
+    type Error = NbtError;
+    type Variant = BinaryDeserializer<'r, 'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(self.variant))?;
+        Ok((
+            variant,
+            BinaryDeserializer::new(self.reader, self.value_type),
+        ))
+    }
+}
+
+impl<'de> VariantAccess<'de> for BinaryDeserializer<'_, 'de> {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<()> {
+        self.reader.skip_tag(self.tag_type)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for NbtTag {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(TAG_TOKEN, TagVisitor)
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = NbtTag;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an NBT value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Byte(v as i8))
+    }
+
+    fn visit_i8<E>(self, v: i8) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Byte(v))
+    }
+
+    fn visit_i16<E>(self, v: i16) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Short(v))
+    }
+
+    fn visit_i32<E>(self, v: i32) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Int(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Long(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<NbtTag, E> {
+        Ok(match i32::try_from(v) {
+            Ok(v) => NbtTag::Int(v),
+            Err(_) => NbtTag::Long(v as i64),
+        })
+    }
+
+    fn visit_f32<E>(self, v: f32) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Float(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::Double(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::ByteArray(v.iter().map(|&b| b as i8).collect()))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::End)
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<NbtTag, E> {
+        Ok(NbtTag::End)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> std::result::Result<NbtTag, D::Error> {
+        NbtTag::deserialize(d)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<NbtTag, D::Error> {
+        d.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<NbtTag, A::Error> {
+        let mut items: Vec<NbtTag> = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element::<NbtTag>()? {
+            if let Some(first) = items.first() {
+                if first.type_id() != item.type_id() {
+                    return Err(de::Error::custom(format!(
+                        "List type mismatch: expected {}, found {}",
+                        first.type_name(),
+                        item.type_name()
+                    )));
+                }
+            }
+            items.push(item);
+        }
+        let tag_type = items.first().map_or(0, |item| item.type_id());
+        Ok(NbtTag::List { tag_type, items })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<NbtTag, A::Error> {
+        let mut compound = CompoundMap::new();
+        let Some(first_key) = map.next_key::<String>()? else {
+            return Ok(NbtTag::Compound(compound));
+        };
+
+        match first_key.as_str() {
+            BYTE_ARRAY_TOKEN => return Ok(NbtTag::ByteArray(map.next_value()?)),
+            INT_ARRAY_TOKEN => return Ok(NbtTag::IntArray(map.next_value()?)),
+            LONG_ARRAY_TOKEN => return Ok(NbtTag::LongArray(map.next_value()?)),
+            _ => {}
+        }
+
+        compound.insert(first_key, map.next_value()?);
+        while let Some((key, value)) = map.next_entry::<String, NbtTag>()? {
+            compound.insert(key, value);
+        }
+        Ok(NbtTag::Compound(compound))
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{format_snbt, CompoundMap, NbtError, NbtPath, NbtTag, PathNode, Result};
+use std::fmt;
+
+// Above this many LCS cells (old × new elements), list gaps are aligned by position
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+/// One difference between two tag trees, addressed by an NBT path
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtChange {
+    /// Compound entry only present in the new tree
+    Added { path: NbtPath, value: NbtTag },
+    /// Compound entry only present in the old tree
+    Removed { path: NbtPath, old: NbtTag },
+    /// Tag replaced by a tag of another type
+    TypeChanged {
+        path: NbtPath,
+        old: NbtTag,
+        new: NbtTag,
+    },
+    /// Value, array or list element type changed, with the same tag type
+    ValueChanged {
+        path: NbtPath,
+        old: NbtTag,
+        new: NbtTag,
+    },
+    /// List element inserted; the path ends with its index after insertion
+    ElementInserted { path: NbtPath, value: NbtTag },
+    /// List element removed; the path ends with its index before removal
+    ElementRemoved { path: NbtPath, old: NbtTag },
+}
+
+impl NbtChange {
+    pub fn path(&self) -> &NbtPath {
+        match self {
+            NbtChange::Added { path, .. }
+            | NbtChange::Removed { path, .. }
+            | NbtChange::TypeChanged { path, .. }
+            | NbtChange::ValueChanged { path, .. }
+            | NbtChange::ElementInserted { path, .. }
+            | NbtChange::ElementRemoved { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for NbtChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path().to_string();
+        let path = if path.is_empty() { "(root)" } else { &path };
+        match self {
+            NbtChange::Added { value, .. } | NbtChange::ElementInserted { value, .. } => {
+                write!(f, "+ {path}: {}", format_snbt(value))
+            }
+            NbtChange::Removed { old, .. } | NbtChange::ElementRemoved { old, .. } => {
+                write!(f, "- {path}: {}", format_snbt(old))
+            }
+            NbtChange::TypeChanged { old, new, .. } => write!(
+                f,
+                "~ {path}: {} -> {} ({} -> {})",
+                format_snbt(old),
+                format_snbt(new),
+                old.type_name(),
+                new.type_name()
+            ),
+            NbtChange::ValueChanged { old, new, .. } => {
+                write!(f, "~ {path}: {} -> {}", format_snbt(old), format_snbt(new))
+            }
+        }
+    }
+}
+
+/// Structural diff between two tags
+///
+/// Compounds are compared by key, so entry order never shows up as a change. Lists are
+/// aligned on their longest common subsequence; elements left over between two aligned
+/// elements are compared pairwise, then reported as removed or inserted. Changes are
+/// ordered so that applying them one after another turns the old tree into the new one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtDiff {
+    changes: Vec<NbtChange>,
+}
+
+impl NbtDiff {
+    pub fn new(old: &NbtTag, new: &NbtTag) -> Self {
+        let mut changes = Vec::new();
+        diff_tags(&mut Vec::new(), old, new, &mut changes);
+        Self { changes }
+    }
+
+    pub fn changes(&self) -> &[NbtChange] {
+        &self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NbtChange> {
+        self.changes.iter()
+    }
+
+    /// Apply the changes as a patch; `target` is left untouched if any path can't be resolved
+    ///
+    /// Old values are not compared, so a diff can be applied to any tree with the same shape.
+    pub fn apply(&self, target: &mut NbtTag) -> Result<()> {
+        let mut patched = target.clone();
+        for change in &self.changes {
+            apply_change(&mut patched, change)?;
+        }
+        *target = patched;
+        Ok(())
+    }
+}
+
+impl fmt::Display for NbtDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for NbtDiff {
+    type Item = NbtChange;
+    type IntoIter = std::vec::IntoIter<NbtChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtDiff {
+    type Item = &'a NbtChange;
+    type IntoIter = std::slice::Iter<'a, NbtChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+/// Changes turning `old` into `new`
+pub fn diff(old: &NbtTag, new: &NbtTag) -> NbtDiff {
+    NbtDiff::new(old, new)
+}
+
+impl NbtTag {
+    // Changes turning self into other
+    pub fn diff(&self, other: &NbtTag) -> NbtDiff {
+        NbtDiff::new(self, other)
+    }
+
+    // Apply a diff as a patch (unchanged on error)
+    pub fn apply_diff(&mut self, diff: &NbtDiff) -> Result<()> {
+        diff.apply(self)
+    }
+}
+
+fn diff_tags(path: &mut Vec<PathNode>, old: &NbtTag, new: &NbtTag, out: &mut Vec<NbtChange>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (NbtTag::Compound(old_map), NbtTag::Compound(new_map)) => {
+            diff_compounds(path, old_map, new_map, out)
+        }
+        (
+            NbtTag::List {
+                tag_type: old_type,
+                items: old_items,
+            },
+            NbtTag::List {
+                tag_type: new_type,
+                items: new_items,
+            },
+        ) if list_patchable(*old_type, old_items, *new_type, new_items) => {
+            diff_lists(path, old_items, new_items, out)
+        }
+        _ if old.type_id() != new.type_id() => out.push(NbtChange::TypeChanged {
+            path: NbtPath::new(path.clone()),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => out.push(NbtChange::ValueChanged {
+            path: NbtPath::new(path.clone()),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+fn diff_compounds(
+    path: &mut Vec<PathNode>,
+    old: &CompoundMap,
+    new: &CompoundMap,
+    out: &mut Vec<NbtChange>,
+) {
+    // Sorted like the canonical form, so the output doesn't depend on entry order
+    let mut keys: Vec<&String> = old
+        .keys()
+        .chain(new.keys().filter(|key| !old.contains_key(*key)))
+        .collect();
+    keys.sort_unstable();
+
+    for key in keys {
+        path.push(PathNode::Child(key.clone()));
+        match (old.get(key), new.get(key)) {
+            (Some(old_value), Some(new_value)) => diff_tags(path, old_value, new_value, out),
+            (Some(old_value), None) => out.push(NbtChange::Removed {
+                path: NbtPath::new(path.clone()),
+                old: old_value.clone(),
+            }),
+            (None, Some(value)) => out.push(NbtChange::Added {
+                path: NbtPath::new(path.clone()),
+                value: value.clone(),
+            }),
+            (None, None) => {}
+        }
+        path.pop();
+    }
+}
+
+// Whether element changes rebuild the new list type: inserting into an empty list takes the
+// type of the first element, and removing the last element resets it to End
+fn list_patchable(old_type: u8, old: &[NbtTag], new_type: u8, new: &[NbtTag]) -> bool {
+    if new.is_empty() {
+        new_type == 0
+    } else {
+        old_type == new_type || old.is_empty()
+    }
+}
+
+fn diff_lists(path: &mut Vec<PathNode>, old: &[NbtTag], new: &[NbtTag], out: &mut Vec<NbtChange>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    // `pos` tracks the element's index in the list as patched so far
+    let mut pos = prefix;
+    let (mut i, mut j) = (0, 0);
+    for (matched_old, matched_new) in lcs(old, new) {
+        diff_gap(
+            path,
+            &old[i..matched_old],
+            &new[j..matched_new],
+            &mut pos,
+            out,
+        );
+        pos += 1;
+        i = matched_old + 1;
+        j = matched_new + 1;
+    }
+    diff_gap(path, &old[i..], &new[j..], &mut pos, out);
+}
+
+// Unaligned elements: changed in place pairwise, then removed or inserted
+fn diff_gap(
+    path: &mut Vec<PathNode>,
+    removed: &[NbtTag],
+    inserted: &[NbtTag],
+    pos: &mut usize,
+    out: &mut Vec<NbtChange>,
+) {
+    let paired = removed.len().min(inserted.len());
+    for (old, new) in removed.iter().zip(inserted) {
+        path.push(PathNode::Index(*pos as i32));
+        diff_tags(path, old, new, out);
+        path.pop();
+        *pos += 1;
+    }
+    for old in &removed[paired..] {
+        path.push(PathNode::Index(*pos as i32));
+        out.push(NbtChange::ElementRemoved {
+            path: NbtPath::new(path.clone()),
+            old: old.clone(),
+        });
+        path.pop();
+    }
+    for value in &inserted[paired..] {
+        path.push(PathNode::Index(*pos as i32));
+        out.push(NbtChange::ElementInserted {
+            path: NbtPath::new(path.clone()),
+            value: value.clone(),
+        });
+        path.pop();
+        *pos += 1;
+    }
+}
+
+// Index pairs of a longest common subsequence, empty when the table would be too large
+fn lcs(old: &[NbtTag], new: &[NbtTag]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    if n == 0 || m == 0 || n.saturating_mul(m) > MAX_LCS_CELLS {
+        return Vec::new();
+    }
+    // table[i * (m + 1) + j] = LCS length of old[i..] and new[j..]
+    let width = m + 1;
+    let mut table = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn apply_change(root: &mut NbtTag, change: &NbtChange) -> Result<()> {
+    let path = change.path();
+    let Some((last, parents)) = path.nodes().split_last() else {
+        return match change {
+            NbtChange::TypeChanged { new, .. } | NbtChange::ValueChanged { new, .. } => {
+                *root = new.clone();
+                Ok(())
+            }
+            _ => Err(unsupported_path(path)),
+        };
+    };
+
+    let mut parent = root;
+    for node in parents {
+        parent = match node {
+            PathNode::Child(key) => parent.get_mut(key),
+            PathNode::Index(index) => match parent {
+                NbtTag::List { items, .. } => {
+                    usize::try_from(*index).ok().and_then(|i| items.get_mut(i))
+                }
+                _ => None,
+            },
+            _ => return Err(unsupported_path(path)),
+        }
+        .ok_or_else(|| NbtError::MissingKey {
+            path: path.to_string(),
+        })?;
+    }
+
+    let result = match (change, last) {
+        (NbtChange::Added { value, .. }, PathNode::Child(key)) => {
+            compound_mut(parent, path)?.insert(key.clone(), value.clone());
+            Ok(())
+        }
+        (NbtChange::Removed { .. }, PathNode::Child(key)) => {
+            compound_mut(parent, path)?;
+            parent
+                .remove(key)
+                .map(drop)
+                .ok_or_else(|| NbtError::MissingKey {
+                    path: path.to_string(),
+                })
+        }
+        (
+            NbtChange::TypeChanged { new, .. } | NbtChange::ValueChanged { new, .. },
+            PathNode::Child(key),
+        ) => {
+            let slot =
+                compound_mut(parent, path)?
+                    .get_mut(key)
+                    .ok_or_else(|| NbtError::MissingKey {
+                        path: path.to_string(),
+                    })?;
+            *slot = new.clone();
+            Ok(())
+        }
+        (
+            NbtChange::TypeChanged { new, .. } | NbtChange::ValueChanged { new, .. },
+            PathNode::Index(index),
+        ) => parent.set_at(list_index(*index), new.clone()).map(drop),
+        (NbtChange::ElementInserted { value, .. }, PathNode::Index(index)) => {
+            parent.insert_at(list_index(*index), value.clone())
+        }
+        (NbtChange::ElementRemoved { .. }, PathNode::Index(index)) => {
+            parent.remove_at(list_index(*index)).map(drop)
+        }
+        _ => Err(unsupported_path(path)),
+    };
+    result.map_err(|error| match error {
+        NbtError::TypeMismatch {
+            expected, found, ..
+        } => NbtError::TypeMismatch {
+            path: path.to_string(),
+            expected,
+            found,
+        },
+        other => other,
+    })
+}
+
+fn compound_mut<'a>(tag: &'a mut NbtTag, path: &NbtPath) -> Result<&'a mut CompoundMap> {
+    let found = tag.type_name();
+    tag.as_compound_mut().ok_or_else(|| NbtError::TypeMismatch {
+        path: path.to_string(),
+        expected: "Compound".to_string(),
+        found: found.to_string(),
+    })
+}
+
+// Negative indices never come out of a diff; map them past any list length
+fn list_index(index: i32) -> usize {
+    usize::try_from(index).unwrap_or(usize::MAX)
+}
+
+fn unsupported_path(path: &NbtPath) -> NbtError {
+    NbtError::InvalidPath {
+        message: format!("\"{path}\" is not a key or index path for this change"),
+        position: 0,
+    }
+}
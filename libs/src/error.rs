@@ -14,6 +14,18 @@ pub enum NbtError {
     #[error("Invalid string length: {0}")]
     InvalidStringLength(usize),
 
+    #[error("Negative length: {0}")]
+    NegativeLength(i32),
+
+    #[error("Nesting depth exceeds the limit of {limit}")]
+    DepthLimitExceeded { limit: usize },
+
+    #[error("Allocation exceeds the limit of {limit} bytes")]
+    AllocationLimitExceeded { limit: usize },
+
+    #[error("Decompressed size exceeds the limit of {limit} bytes")]
+    DecompressedSizeExceeded { limit: usize },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -38,6 +50,34 @@ pub enum NbtError {
     #[error("SNBT type mismatch in list: expected {expected}, found {found}")]
     SnbtListTypeMismatch { expected: String, found: String },
 
+    #[error("{}expected {expected}, found {found}", path_prefix(.path))]
+    TypeMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+
+    #[error("{}missing key", path_prefix(.path))]
+    MissingKey { path: String },
+
+    #[error("Invalid writer state: {0}")]
+    WriterState(String),
+
+    #[error("Invalid NBT path: {message} at position {position}")]
+    InvalidPath { message: String, position: usize },
+
+    #[error("Index {index} out of bounds for length {len}")]
+    IndexOutOfBounds { index: i64, len: usize },
+
+    #[error("Item component {0} has no legacy tag equivalent")]
+    UnsupportedComponent(String),
+
+    #[error("Serde error: {0}")]
+    Serde(String),
+
+    #[error("JSON conversion error: {0}")]
+    Json(String),
+
     #[error("Invalid region data: {0}")]
     InvalidRegionData(String),
 
@@ -50,6 +90,14 @@ pub enum NbtError {
 
 pub type Result<T> = std::result::Result<T, NbtError>;
 
+fn path_prefix(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("{path}: ")
+    }
+}
+
 impl NbtError {
     pub fn snbt_parse_error(message: impl Into<String>, position: usize) -> Self {
         Self::SnbtParse {
@@ -65,4 +113,50 @@ impl NbtError {
     pub fn region_error(message: impl Into<String>) -> Self {
         Self::InvalidRegionData(message.into())
     }
+
+    pub fn type_mismatch(expected: impl Into<String>, found: impl Into<String>) -> Self {
+        Self::TypeMismatch {
+            path: String::new(),
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    /// Prefix the error path with a compound key
+    pub fn at_key(self, key: &str) -> Self {
+        let key = if key.is_empty()
+            || key
+                .chars()
+                .any(|c| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '+'))
+        {
+            format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+        } else {
+            key.to_string()
+        };
+        self.prefix_path(|path| {
+            if path.is_empty() || path.starts_with('[') {
+                format!("{key}{path}")
+            } else {
+                format!("{key}.{path}")
+            }
+        })
+    }
+
+    /// Prefix the error path with a list index
+    pub fn at_index(self, index: usize) -> Self {
+        self.prefix_path(|path| {
+            if path.is_empty() || path.starts_with('[') {
+                format!("[{index}]{path}")
+            } else {
+                format!("[{index}].{path}")
+            }
+        })
+    }
+
+    fn prefix_path(mut self, prefix: impl FnOnce(&str) -> String) -> Self {
+        if let Self::TypeMismatch { path, .. } | Self::MissingKey { path } = &mut self {
+            *path = prefix(path);
+        }
+        self
+    }
 }
@@ -0,0 +1,287 @@
+use crate::reader::{decode_i32s, decode_i64s};
+use crate::{Endian, NbtError, NbtReader, Result};
+
+macro_rules! array_ref {
+    ($(#[$doc:meta])* $name:ident, $ty:ty, $size:expr, $bulk:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name<'a> {
+            bytes: &'a [u8],
+            endian: Endian,
+        }
+
+        impl<'a> $name<'a> {
+            /// Wrap raw array bytes (length must be a multiple of the element size)
+            pub fn new(bytes: &'a [u8], endian: Endian) -> Self {
+                debug_assert_eq!(bytes.len() % $size, 0);
+                Self { bytes, endian }
+            }
+
+            pub fn len(&self) -> usize {
+                self.bytes.len() / $size
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.bytes.is_empty()
+            }
+
+            /// Raw bytes in the file's endianness
+            pub fn as_bytes(&self) -> &'a [u8] {
+                self.bytes
+            }
+
+            pub fn get(&self, index: usize) -> Option<$ty> {
+                let chunk = self.bytes.get(index * $size..(index + 1) * $size)?;
+                Some(Self::decode(chunk.try_into().unwrap(), self.endian))
+            }
+
+            pub fn iter(&self) -> impl Iterator<Item = $ty> + 'a {
+                let endian = self.endian;
+                self.bytes
+                    .chunks_exact($size)
+                    .map(move |chunk| Self::decode(chunk.try_into().unwrap(), endian))
+            }
+
+            pub fn to_vec(&self) -> Vec<$ty> {
+                $bulk(self.bytes, self.endian)
+            }
+
+            fn decode(bytes: [u8; $size], endian: Endian) -> $ty {
+                match endian {
+                    Endian::Big => <$ty>::from_be_bytes(bytes),
+                    Endian::Little => <$ty>::from_le_bytes(bytes),
+                }
+            }
+        }
+    };
+}
+
+array_ref!(
+    /// Borrowed `IntArray` payload, decoded on access
+    IntArrayRef,
+    i32,
+    4,
+    decode_i32s
+);
+array_ref!(
+    /// Borrowed `LongArray` payload, decoded on access
+    LongArrayRef,
+    i64,
+    8,
+    decode_i64s
+);
+
+/// Event produced by `NbtEventReader`
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtEvent<'a> {
+    /// Name of the next value (compound entry or named root)
+    Key(&'a str),
+    StartCompound,
+    StartList {
+        element_type: u8,
+        len: usize,
+    },
+    /// End of the innermost compound or list
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(&'a str),
+    ByteArray(&'a [u8]),
+    IntArray(IntArrayRef<'a>),
+    LongArray(LongArrayRef<'a>),
+}
+
+enum Frame {
+    Compound,
+    List { element_type: u8, remaining: usize },
+}
+
+/// Pull-based (StAX-style) reader over binary NBT.
+///
+/// Strings and arrays borrow from the input, nothing is allocated per value.
+/// Call `skip_value` after a `Key` (or inside a list) to jump over a subtree,
+/// or `skip_container` to leave the current compound/list.
+pub struct NbtEventReader<'a> {
+    reader: NbtReader<'a>,
+    stack: Vec<Frame>,
+    pending: Option<u8>,
+    named_root: bool,
+    started: bool,
+}
+
+impl<'a> NbtEventReader<'a> {
+    /// Reader for a named root tag (files, region chunks)
+    pub fn new(data: &'a [u8], endian: Endian) -> Self {
+        Self {
+            reader: NbtReader::new(data, endian),
+            stack: Vec::new(),
+            pending: None,
+            named_root: true,
+            started: false,
+        }
+    }
+
+    /// Reader for a root tag without name (network NBT since 1.20.2)
+    pub fn unnamed(data: &'a [u8], endian: Endian) -> Self {
+        Self {
+            named_root: false,
+            ..Self::new(data, endian)
+        }
+    }
+
+    /// Number of open compounds and lists
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Byte offset in the input
+    pub fn position(&self) -> usize {
+        self.reader.cursor
+    }
+
+    pub fn next_event(&mut self) -> Result<Option<NbtEvent<'a>>> {
+        if let Some(tag_type) = self.pending.take() {
+            return self.read_value(tag_type).map(Some);
+        }
+
+        match self.stack.last_mut() {
+            None if self.started => Ok(None),
+            None => {
+                self.started = true;
+                let tag_type = self.reader.read_u8()?;
+                if tag_type == 0 {
+                    return Ok(None);
+                }
+                if self.named_root {
+                    let name = self.reader.read_str()?;
+                    self.pending = Some(tag_type);
+                    Ok(Some(NbtEvent::Key(name)))
+                } else {
+                    self.read_value(tag_type).map(Some)
+                }
+            }
+            Some(Frame::Compound) => {
+                let tag_type = self.reader.read_u8()?;
+                if tag_type == 0 {
+                    self.stack.pop();
+                    return Ok(Some(NbtEvent::End));
+                }
+                let name = self.reader.read_str()?;
+                self.pending = Some(tag_type);
+                Ok(Some(NbtEvent::Key(name)))
+            }
+            Some(Frame::List {
+                element_type,
+                remaining,
+            }) => {
+                if *remaining == 0 {
+                    self.stack.pop();
+                    return Ok(Some(NbtEvent::End));
+                }
+                *remaining -= 1;
+                let element_type = *element_type;
+                self.read_value(element_type).map(Some)
+            }
+        }
+    }
+
+    /// Skip the next value: the one after a `Key`, or the next list element
+    pub fn skip_value(&mut self) -> Result<()> {
+        if let Some(tag_type) = self.pending.take() {
+            return self.reader.skip_tag(tag_type);
+        }
+        match self.stack.last_mut() {
+            Some(Frame::List {
+                element_type,
+                remaining,
+            }) if *remaining > 0 => {
+                *remaining -= 1;
+                let element_type = *element_type;
+                self.reader.skip_tag(element_type)
+            }
+            _ => Err(NbtError::Parse("No value to skip".to_string())),
+        }
+    }
+
+    /// Skip the rest of the innermost compound or list, including its `End`
+    pub fn skip_container(&mut self) -> Result<()> {
+        if let Some(tag_type) = self.pending.take() {
+            self.reader.skip_tag(tag_type)?;
+        }
+        match self.stack.pop() {
+            Some(Frame::Compound) => self.reader.skip_tag(10),
+            Some(Frame::List {
+                element_type,
+                remaining,
+            }) => {
+                for _ in 0..remaining {
+                    self.reader.skip_tag(element_type)?;
+                }
+                Ok(())
+            }
+            None => Err(NbtError::Parse("Not inside a compound or list".to_string())),
+        }
+    }
+
+    fn read_value(&mut self, tag_type: u8) -> Result<NbtEvent<'a>> {
+        let endian = self.reader.endian();
+        Ok(match tag_type {
+            1 => NbtEvent::Byte(self.reader.read_i8()?),
+            2 => NbtEvent::Short(self.reader.read_i16()?),
+            3 => NbtEvent::Int(self.reader.read_i32()?),
+            4 => NbtEvent::Long(self.reader.read_i64()?),
+            5 => NbtEvent::Float(self.reader.read_f32()?),
+            6 => NbtEvent::Double(self.reader.read_f64()?),
+            7 => NbtEvent::ByteArray(self.reader.read_array_bytes(1)?),
+            8 => NbtEvent::String(self.reader.read_str()?),
+            9 => {
+                let element_type = self.reader.read_u8()?;
+                let len = self.reader.read_len()?;
+                self.check_depth()?;
+                self.stack.push(Frame::List {
+                    element_type,
+                    remaining: len,
+                });
+                NbtEvent::StartList { element_type, len }
+            }
+            10 => {
+                self.check_depth()?;
+                self.stack.push(Frame::Compound);
+                NbtEvent::StartCompound
+            }
+            11 => NbtEvent::IntArray(IntArrayRef::new(self.reader.read_array_bytes(4)?, endian)),
+            12 => NbtEvent::LongArray(LongArrayRef::new(self.reader.read_array_bytes(8)?, endian)),
+            _ => return Err(NbtError::InvalidTagType(tag_type)),
+        })
+    }
+
+    fn check_depth(&self) -> Result<()> {
+        let limit = self.reader.limits().max_depth;
+        if self.stack.len() >= limit {
+            return Err(NbtError::DepthLimitExceeded { limit });
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for NbtEventReader<'a> {
+    type Item = Result<NbtEvent<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => None,
+            Err(e) => {
+                // Stop after the first error
+                self.stack.clear();
+                self.pending = None;
+                self.started = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
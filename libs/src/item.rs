@@ -0,0 +1,583 @@
+use crate::{CompoundMap, FromNbt, NbtError, NbtTag, Result, ToNbt};
+
+const CUSTOM_NAME: &str = "minecraft:custom_name";
+const LORE: &str = "minecraft:lore";
+const ENCHANTMENTS: &str = "minecraft:enchantments";
+const STORED_ENCHANTMENTS: &str = "minecraft:stored_enchantments";
+const DAMAGE: &str = "minecraft:damage";
+const CUSTOM_DATA: &str = "minecraft:custom_data";
+const UNBREAKABLE: &str = "minecraft:unbreakable";
+const REPAIR_COST: &str = "minecraft:repair_cost";
+const CUSTOM_MODEL_DATA: &str = "minecraft:custom_model_data";
+
+/// Item NBT layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemFormat {
+    /// Before 1.20.5: `{id, Count: byte, tag: {...}}`
+    Legacy,
+    /// 1.20.5 and later: `{id, count: int, components: {...}}`
+    Components,
+}
+
+/// Item stack over its NBT compound, reading and editing both item layouts
+///
+/// Edits are made in the item's own layout and other keys are kept as they are. Text
+/// components (name, lore) are returned as stored: JSON strings, or compounds in newer versions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    map: CompoundMap,
+}
+
+impl ItemStack {
+    /// Item in the component layout
+    pub fn new(id: impl Into<String>, count: i32) -> Self {
+        let mut map = CompoundMap::new();
+        map.insert("id".to_string(), NbtTag::String(id.into()));
+        map.insert("count".to_string(), NbtTag::Int(count));
+        Self { map }
+    }
+
+    /// Wrap an item compound, which must have a string `id`
+    pub fn from_compound(map: CompoundMap) -> Result<Self> {
+        match map.get("id") {
+            Some(NbtTag::String(_)) => Ok(Self { map }),
+            Some(other) => Err(NbtError::type_mismatch("String", other.type_name()).at_key("id")),
+            None => Err(NbtError::MissingKey {
+                path: String::new(),
+            }
+            .at_key("id")),
+        }
+    }
+
+    pub fn as_compound(&self) -> &CompoundMap {
+        &self.map
+    }
+
+    pub fn into_compound(self) -> CompoundMap {
+        self.map
+    }
+
+    pub fn format(&self) -> ItemFormat {
+        if self.map.contains_key("Count") || self.map.contains_key("tag") {
+            ItemFormat::Legacy
+        } else {
+            ItemFormat::Components
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.map.get("id").map(NbtTag::as_string).unwrap_or("")
+    }
+
+    /// Stack size, 1 when not stored
+    pub fn count(&self) -> i32 {
+        match self.map.get("Count").or_else(|| self.map.get("count")) {
+            Some(count) => count.as_number() as i32,
+            None => 1,
+        }
+    }
+
+    /// Set the stack size; legacy items fail for counts that don't fit the `Count` byte
+    pub fn set_count(&mut self, count: i32) -> Result<()> {
+        match self.format() {
+            ItemFormat::Legacy => self.map.insert(
+                "Count".to_string(),
+                NbtTag::Byte(legacy_count(count as i64)?),
+            ),
+            ItemFormat::Components => self.map.insert("count".to_string(), NbtTag::Int(count)),
+        };
+        Ok(())
+    }
+
+    /// Custom name text component
+    pub fn name(&self) -> Option<&NbtTag> {
+        match self.format() {
+            ItemFormat::Legacy => self.legacy_tag()?.get("display")?.get("Name"),
+            ItemFormat::Components => self.components()?.get(CUSTOM_NAME),
+        }
+    }
+
+    pub fn set_name(&mut self, name: impl Into<NbtTag>) {
+        let name = name.into();
+        match self.format() {
+            ItemFormat::Legacy => self.display_mut().insert("Name".to_string(), name),
+            ItemFormat::Components => self.components_mut().insert(CUSTOM_NAME.to_string(), name),
+        };
+    }
+
+    /// Lore lines as text components
+    pub fn lore(&self) -> Vec<&NbtTag> {
+        let lore = match self.format() {
+            ItemFormat::Legacy => self
+                .legacy_tag()
+                .and_then(|tag| tag.get("display")?.get("Lore")),
+            ItemFormat::Components => self.components().and_then(|c| c.get(LORE)),
+        };
+        match lore.and_then(NbtTag::as_list) {
+            Some((_, lines)) => lines.iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Replace the lore; every line must have the same tag type
+    pub fn set_lore(&mut self, lines: Vec<NbtTag>) -> Result<()> {
+        let lore = NbtTag::list_of(lines)?;
+        match self.format() {
+            ItemFormat::Legacy => self.display_mut().insert("Lore".to_string(), lore),
+            ItemFormat::Components => self.components_mut().insert(LORE.to_string(), lore),
+        };
+        Ok(())
+    }
+
+    /// Enchantment ids and levels
+    pub fn enchantments(&self) -> Vec<(String, i32)> {
+        match self.format() {
+            ItemFormat::Legacy => self
+                .legacy_tag()
+                .and_then(|tag| tag.get("Enchantments"))
+                .map(legacy_enchantments)
+                .unwrap_or_default(),
+            ItemFormat::Components => self
+                .components()
+                .and_then(|c| c.get(ENCHANTMENTS))
+                .map(component_enchantments)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn enchantment_level(&self, id: &str) -> Option<i32> {
+        self.enchantments()
+            .into_iter()
+            .find(|(enchantment, _)| same_id(enchantment, id))
+            .map(|(_, level)| level)
+    }
+
+    /// Add an enchantment or change its level
+    pub fn set_enchantment(&mut self, id: &str, level: i32) {
+        match self.format() {
+            ItemFormat::Legacy => {
+                let list =
+                    compound_entry(self.legacy_tag_mut(), "Enchantments", || NbtTag::list(10));
+                let level = NbtTag::Short(level as i16);
+                if let Some((_, items)) = list.as_list_mut() {
+                    let existing = items
+                        .iter_mut()
+                        .find(|item| same_id(item.get_string("id"), id));
+                    if let Some(NbtTag::Compound(enchantment)) = existing {
+                        enchantment.insert("lvl".to_string(), level);
+                        return;
+                    }
+                }
+                let enchantment = NbtTag::builder().with("id", id).with("lvl", level).build();
+                if list.push(enchantment.clone()).is_err() {
+                    // Not a list of compounds: start over
+                    *list = NbtTag::List {
+                        tag_type: 10,
+                        items: vec![enchantment],
+                    };
+                }
+            }
+            ItemFormat::Components => {
+                let levels = component_levels_mut(self.components_mut());
+                let key = levels
+                    .keys()
+                    .find(|key| same_id(key, id))
+                    .cloned()
+                    .unwrap_or_else(|| id.to_string());
+                levels.insert(key, NbtTag::Int(level));
+            }
+        }
+    }
+
+    /// Remove an enchantment (returns whether it was present)
+    pub fn remove_enchantment(&mut self, id: &str) -> bool {
+        match self.format() {
+            ItemFormat::Legacy => {
+                let list = self
+                    .map
+                    .get_mut("tag")
+                    .and_then(|tag| tag.get_mut("Enchantments"));
+                let Some(NbtTag::List { tag_type, items }) = list else {
+                    return false;
+                };
+                let before = items.len();
+                items.retain(|item| !same_id(item.get_string("id"), id));
+                if items.is_empty() {
+                    *tag_type = 0;
+                }
+                items.len() != before
+            }
+            ItemFormat::Components => {
+                let Some(component) = self
+                    .map
+                    .get_mut("components")
+                    .and_then(|c| c.get_mut(ENCHANTMENTS))
+                else {
+                    return false;
+                };
+                let levels = match component.get("levels") {
+                    Some(NbtTag::Compound(_)) => {
+                        component.get_mut("levels").expect("checked above")
+                    }
+                    _ => component,
+                };
+                let key = match levels.as_compound() {
+                    Some(map) => map.keys().find(|key| same_id(key, id)).cloned(),
+                    None => None,
+                };
+                key.and_then(|key| levels.remove(&key)).is_some()
+            }
+        }
+    }
+
+    /// Durability damage, 0 when not stored
+    pub fn damage(&self) -> i32 {
+        let damage = match self.format() {
+            ItemFormat::Legacy => self.legacy_tag().and_then(|tag| tag.get("Damage")),
+            ItemFormat::Components => self.components().and_then(|c| c.get(DAMAGE)),
+        };
+        damage.map(|tag| tag.as_number() as i32).unwrap_or(0)
+    }
+
+    pub fn set_damage(&mut self, damage: i32) {
+        match self.format() {
+            ItemFormat::Legacy => self
+                .legacy_tag_mut()
+                .insert("Damage".to_string(), NbtTag::Int(damage)),
+            ItemFormat::Components => self
+                .components_mut()
+                .insert(DAMAGE.to_string(), NbtTag::Int(damage)),
+        };
+    }
+
+    /// Custom (plugin or datapack) data: `minecraft:custom_data`, or the legacy `tag`
+    /// entries that `to_components` moves there
+    pub fn custom_data(&self) -> CompoundMap {
+        match self.format() {
+            ItemFormat::Legacy => self
+                .legacy_tag()
+                .map(|tag| split_legacy_tag(tag).1)
+                .unwrap_or_default(),
+            ItemFormat::Components => self
+                .components()
+                .and_then(|c| c.get(CUSTOM_DATA)?.as_compound())
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set_custom_data(&mut self, key: impl Into<String>, value: impl Into<NbtTag>) {
+        let target = match self.format() {
+            ItemFormat::Legacy => self.legacy_tag_mut(),
+            ItemFormat::Components => compound_map(compound_entry(
+                self.components_mut(),
+                CUSTOM_DATA,
+                NbtTag::compound,
+            )),
+        };
+        target.insert(key.into(), value.into());
+    }
+
+    /// Same item in the 1.20.5 component layout, like the vanilla item data fix
+    ///
+    /// `tag` entries without a component equivalent move to `minecraft:custom_data`.
+    pub fn to_components(&self) -> ItemStack {
+        if self.format() == ItemFormat::Components {
+            return self.clone();
+        }
+        let mut map = CompoundMap::new();
+        for (key, value) in &self.map {
+            match key.as_str() {
+                "Count" => {
+                    map.insert("count".to_string(), NbtTag::Int(value.as_number() as i32));
+                }
+                "tag" => {}
+                _ => {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        let (mut components, custom) = self.legacy_tag().map(split_legacy_tag).unwrap_or_default();
+        if !custom.is_empty() {
+            insert(&mut components, CUSTOM_DATA, NbtTag::Compound(custom));
+        }
+        if !components.is_empty() {
+            insert(&mut map, "components", NbtTag::Compound(components));
+        }
+        ItemStack { map }
+    }
+
+    /// Same item in the legacy `tag` layout
+    ///
+    /// Fails for components that have no legacy equivalent and for counts above 127.
+    pub fn to_legacy(&self) -> Result<ItemStack> {
+        if self.format() == ItemFormat::Legacy {
+            return Ok(self.clone());
+        }
+        let mut map = CompoundMap::new();
+        for (key, value) in &self.map {
+            match key.as_str() {
+                "count" => {
+                    let count = legacy_count(value.as_number() as i64)?;
+                    map.insert("Count".to_string(), NbtTag::Byte(count));
+                }
+                "components" => {}
+                _ => {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        if !map.contains_key("Count") {
+            map.insert("Count".to_string(), NbtTag::Byte(1));
+        }
+
+        let mut tag = CompoundMap::new();
+        let mut display = CompoundMap::new();
+        for (key, value) in self.components().into_iter().flatten() {
+            let id = key.strip_prefix("minecraft:").unwrap_or(key);
+            match id {
+                "damage" => insert(&mut tag, "Damage", NbtTag::Int(value.as_number() as i32)),
+                "repair_cost" => insert(
+                    &mut tag,
+                    "RepairCost",
+                    NbtTag::Int(value.as_number() as i32),
+                ),
+                "custom_model_data" if value.is_number() => insert(
+                    &mut tag,
+                    "CustomModelData",
+                    NbtTag::Int(value.as_number() as i32),
+                ),
+                "unbreakable" => insert(&mut tag, "Unbreakable", NbtTag::Byte(1)),
+                "enchantments" => insert(&mut tag, "Enchantments", enchantment_list(value)),
+                "stored_enchantments" => {
+                    insert(&mut tag, "StoredEnchantments", enchantment_list(value))
+                }
+                "custom_name" => insert(&mut display, "Name", value.clone()),
+                "lore" => insert(&mut display, "Lore", value.clone()),
+                "custom_data" if value.is_compound() => {
+                    for (custom_key, custom_value) in value.as_compound().into_iter().flatten() {
+                        match (custom_key.as_str(), custom_value) {
+                            ("display", NbtTag::Compound(rest)) => {
+                                for (display_key, display_value) in rest {
+                                    display
+                                        .entry(display_key.clone())
+                                        .or_insert_with(|| display_value.clone());
+                                }
+                            }
+                            _ => insert(&mut tag, custom_key, custom_value.clone()),
+                        }
+                    }
+                }
+                _ => return Err(NbtError::UnsupportedComponent(key.clone())),
+            }
+        }
+        if !display.is_empty() {
+            insert(&mut tag, "display", NbtTag::Compound(display));
+        }
+        if !tag.is_empty() {
+            insert(&mut map, "tag", NbtTag::Compound(tag));
+        }
+        Ok(ItemStack { map })
+    }
+
+    fn legacy_tag(&self) -> Option<&CompoundMap> {
+        self.map.get("tag")?.as_compound()
+    }
+
+    fn components(&self) -> Option<&CompoundMap> {
+        self.map.get("components")?.as_compound()
+    }
+
+    fn legacy_tag_mut(&mut self) -> &mut CompoundMap {
+        compound_map(compound_entry(&mut self.map, "tag", NbtTag::compound))
+    }
+
+    fn components_mut(&mut self) -> &mut CompoundMap {
+        compound_map(compound_entry(
+            &mut self.map,
+            "components",
+            NbtTag::compound,
+        ))
+    }
+
+    fn display_mut(&mut self) -> &mut CompoundMap {
+        compound_map(compound_entry(
+            self.legacy_tag_mut(),
+            "display",
+            NbtTag::compound,
+        ))
+    }
+}
+
+impl FromNbt for ItemStack {
+    fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        match tag {
+            NbtTag::Compound(map) => Self::from_compound(map.clone()),
+            other => Err(NbtError::type_mismatch("Compound", other.type_name())),
+        }
+    }
+}
+
+impl ToNbt for ItemStack {
+    fn to_nbt(&self) -> NbtTag {
+        NbtTag::Compound(self.map.clone())
+    }
+}
+
+impl From<ItemStack> for NbtTag {
+    fn from(item: ItemStack) -> Self {
+        NbtTag::Compound(item.map)
+    }
+}
+
+fn legacy_count(count: i64) -> Result<i8> {
+    i8::try_from(count).map_err(|_| {
+        NbtError::InvalidNumber(format!("count {count} does not fit the legacy Count byte"))
+    })
+}
+
+// Legacy `tag` entries split into components and the rest (custom data), the one rule
+// shared by `custom_data` and `to_components`. `Unbreakable:0b` is dropped like vanilla does
+fn split_legacy_tag(tag: &CompoundMap) -> (CompoundMap, CompoundMap) {
+    let mut components = CompoundMap::new();
+    let mut custom = CompoundMap::new();
+    for (key, value) in tag {
+        match key.as_str() {
+            "Damage" => insert(
+                &mut components,
+                DAMAGE,
+                NbtTag::Int(value.as_number() as i32),
+            ),
+            "RepairCost" => insert(
+                &mut components,
+                REPAIR_COST,
+                NbtTag::Int(value.as_number() as i32),
+            ),
+            "CustomModelData" => insert(
+                &mut components,
+                CUSTOM_MODEL_DATA,
+                NbtTag::Int(value.as_number() as i32),
+            ),
+            "Unbreakable" => {
+                if value.as_number() != 0.0 {
+                    insert(&mut components, UNBREAKABLE, NbtTag::compound());
+                }
+            }
+            "Enchantments" => insert(&mut components, ENCHANTMENTS, levels_component(value)),
+            "StoredEnchantments" => insert(
+                &mut components,
+                STORED_ENCHANTMENTS,
+                levels_component(value),
+            ),
+            "display" if value.is_compound() => {
+                let mut rest = CompoundMap::new();
+                for (display_key, display_value) in value.as_compound().into_iter().flatten() {
+                    match display_key.as_str() {
+                        "Name" => insert(&mut components, CUSTOM_NAME, display_value.clone()),
+                        "Lore" => insert(&mut components, LORE, display_value.clone()),
+                        _ => insert(&mut rest, display_key, display_value.clone()),
+                    }
+                }
+                if !rest.is_empty() {
+                    insert(&mut custom, "display", NbtTag::Compound(rest));
+                }
+            }
+            _ => insert(&mut custom, key, value.clone()),
+        }
+    }
+    (components, custom)
+}
+
+fn same_id(a: &str, b: &str) -> bool {
+    a.strip_prefix("minecraft:").unwrap_or(a) == b.strip_prefix("minecraft:").unwrap_or(b)
+}
+
+fn insert(map: &mut CompoundMap, key: &str, value: NbtTag) {
+    map.insert(key.to_string(), value);
+}
+
+// Entry `key` of `map`, replaced by `create()` when missing or of another type
+fn compound_entry<'a>(
+    map: &'a mut CompoundMap,
+    key: &str,
+    create: fn() -> NbtTag,
+) -> &'a mut NbtTag {
+    let expected = create().type_id();
+    let entry = map.entry(key.to_string()).or_insert_with(create);
+    if entry.type_id() != expected {
+        *entry = create();
+    }
+    entry
+}
+
+fn compound_map(tag: &mut NbtTag) -> &mut CompoundMap {
+    tag.as_compound_mut()
+        .expect("compound_entry with a compound")
+}
+
+// `{levels: {...}}` in 1.20.5, the level map itself from 1.21.5
+fn component_levels(component: &NbtTag) -> Option<&CompoundMap> {
+    match component.get("levels") {
+        Some(levels) => levels.as_compound(),
+        None => component.as_compound(),
+    }
+}
+
+fn component_levels_mut(components: &mut CompoundMap) -> &mut CompoundMap {
+    let component = compound_entry(components, ENCHANTMENTS, || {
+        NbtTag::builder().with("levels", NbtTag::compound()).build()
+    });
+    let component = compound_map(component);
+    if matches!(component.get("levels"), Some(NbtTag::Compound(_))) {
+        return compound_map(component.get_mut("levels").expect("checked above"));
+    }
+    component
+}
+
+fn component_enchantments(component: &NbtTag) -> Vec<(String, i32)> {
+    component_levels(component)
+        .into_iter()
+        .flatten()
+        .map(|(id, level)| (id.clone(), level.as_number() as i32))
+        .collect()
+}
+
+fn legacy_enchantments(list: &NbtTag) -> Vec<(String, i32)> {
+    match list.as_list() {
+        Some((_, items)) => items
+            .iter()
+            .map(|item| {
+                (
+                    item.get_string("id").to_string(),
+                    item.get_number("lvl") as i32,
+                )
+            })
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn levels_component(list: &NbtTag) -> NbtTag {
+    let levels: CompoundMap = legacy_enchantments(list)
+        .into_iter()
+        .map(|(id, level)| (id, NbtTag::Int(level)))
+        .collect();
+    NbtTag::builder().with("levels", levels).build()
+}
+
+fn enchantment_list(component: &NbtTag) -> NbtTag {
+    let items = component_enchantments(component)
+        .into_iter()
+        .map(|(id, level)| {
+            NbtTag::builder()
+                .with("id", id)
+                .with("lvl", NbtTag::Short(level as i16))
+                .build()
+        })
+        .collect();
+    NbtTag::List {
+        tag_type: 10,
+        items,
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{CompoundMap, NbtError, NbtTag, Result};
+use serde_json::{Map, Number, Value};
+
+/// Strategy used to convert between JSON and NBT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonMapping {
+    /// Lossy natural mapping: integers become Int (or Long when out of
+    /// range), decimals become Double and booleans become Byte.
+    /// Mixed numeric lists are widened, other mixed lists are rejected.
+    #[default]
+    Natural,
+    /// Lossless mapping where every tag is `{"type": "...", "value": ...}`
+    Typed,
+    /// Same semantics as Minecraft's `JsonOps` and `NbtOps`: every JSON
+    /// number becomes a Double, boolean lists collapse into ByteArray, and
+    /// heterogeneous lists wrap their elements in `{"": value}` compounds.
+    Vanilla,
+}
+
+/// Convert NBT tag to JSON value
+pub fn nbt_to_json(tag: &NbtTag, mapping: JsonMapping) -> Value {
+    match mapping {
+        JsonMapping::Natural => natural_to_json(tag, false),
+        JsonMapping::Vanilla => natural_to_json(tag, true),
+        JsonMapping::Typed => typed_to_json(tag),
+    }
+}
+
+/// Convert JSON value to NBT tag
+pub fn json_to_nbt(value: &Value, mapping: JsonMapping) -> Result<NbtTag> {
+    match mapping {
+        JsonMapping::Natural => natural_from_json(value),
+        JsonMapping::Vanilla => Ok(vanilla_from_json(value)),
+        JsonMapping::Typed => typed_from_json(value),
+    }
+}
+
+impl NbtTag {
+    // Convert to JSON value
+    pub fn to_json(&self, mapping: JsonMapping) -> Value {
+        nbt_to_json(self, mapping)
+    }
+
+    // Create from JSON value
+    pub fn from_json(value: &Value, mapping: JsonMapping) -> Result<Self> {
+        json_to_nbt(value, mapping)
+    }
+}
+
+fn float_to_json(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+fn natural_to_json(tag: &NbtTag, vanilla: bool) -> Value {
+    match tag {
+        NbtTag::End => Value::Null,
+        NbtTag::Byte(v) => Value::from(*v),
+        NbtTag::Short(v) => Value::from(*v),
+        NbtTag::Int(v) => Value::from(*v),
+        NbtTag::Long(v) => Value::from(*v),
+        // Go through the shortest decimal form so 0.1f stays 0.1
+        NbtTag::Float(v) => float_to_json(v.to_string().parse().unwrap_or(*v as f64)),
+        NbtTag::Double(v) => float_to_json(*v),
+        NbtTag::ByteArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+        NbtTag::String(s) => Value::String(s.clone()),
+        NbtTag::List { items, .. } => items
+            .iter()
+            .map(|item| match item {
+                NbtTag::Compound(map) if vanilla && is_wrapper(map) => {
+                    natural_to_json(&map[""], vanilla)
+                }
+                item => natural_to_json(item, vanilla),
+            })
+            .collect(),
+        NbtTag::Compound(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), natural_to_json(value, vanilla)))
+                .collect(),
+        ),
+        NbtTag::IntArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+        NbtTag::LongArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+    }
+}
+
+fn natural_from_json(value: &Value) -> Result<NbtTag> {
+    Ok(match value {
+        Value::Null => NbtTag::End,
+        Value::Bool(b) => NbtTag::Byte(*b as i8),
+        Value::Number(n) => match n.as_i64() {
+            Some(v) => match i32::try_from(v) {
+                Ok(v) => NbtTag::Int(v),
+                Err(_) => NbtTag::Long(v),
+            },
+            None => NbtTag::Double(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => NbtTag::String(s.clone()),
+        Value::Array(values) => {
+            let items = values
+                .iter()
+                .map(natural_from_json)
+                .collect::<Result<Vec<_>>>()?;
+            natural_list(items)?
+        }
+        Value::Object(map) => {
+            let mut compound = CompoundMap::with_capacity(map.len());
+            for (key, value) in map {
+                if !value.is_null() {
+                    compound.insert(key.clone(), natural_from_json(value)?);
+                }
+            }
+            NbtTag::Compound(compound)
+        }
+    })
+}
+
+fn natural_list(items: Vec<NbtTag>) -> Result<NbtTag> {
+    let Some(first) = items.first() else {
+        return Ok(NbtTag::list(0));
+    };
+
+    let mut tag_type = first.type_id();
+    for item in &items {
+        let item_type = item.type_id();
+        if item_type == tag_type {
+            continue;
+        }
+        // Widen Byte < Int < Long < Double for mixed numbers
+        if matches!(item_type, 1 | 3 | 4 | 6) && matches!(tag_type, 1 | 3 | 4 | 6) {
+            tag_type = tag_type.max(item_type);
+            continue;
+        }
+        return Err(NbtError::ListTypeMismatch {
+            expected: tag_type,
+            found: item_type,
+        });
+    }
+
+    let items = items
+        .into_iter()
+        .map(|item| match tag_type {
+            3 if item.type_id() != 3 => NbtTag::Int(item.as_number() as i32),
+            4 => match item {
+                NbtTag::Long(v) => NbtTag::Long(v),
+                item => NbtTag::Long(item.as_number() as i64),
+            },
+            6 => NbtTag::Double(item.as_number()),
+            _ => item,
+        })
+        .collect();
+    Ok(NbtTag::List { tag_type, items })
+}
+
+fn is_wrapper(map: &CompoundMap) -> bool {
+    map.len() == 1 && map.contains_key("")
+}
+
+fn vanilla_from_json(value: &Value) -> NbtTag {
+    match value {
+        Value::Null => NbtTag::End,
+        Value::Bool(b) => NbtTag::Byte(*b as i8),
+        Value::Number(n) => NbtTag::Double(n.as_f64().unwrap_or(0.0)),
+        Value::String(s) => NbtTag::String(s.clone()),
+        Value::Array(values) => vanilla_list(values.iter().map(vanilla_from_json).collect()),
+        Value::Object(map) => NbtTag::Compound(
+            map.iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), vanilla_from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+// Mirrors NbtOps.ListCollector
+fn vanilla_list(items: Vec<NbtTag>) -> NbtTag {
+    let Some(first) = items.first() else {
+        return NbtTag::list(0);
+    };
+
+    let tag_type = first.type_id();
+    if items.iter().all(|item| item.type_id() == tag_type) {
+        return match tag_type {
+            1 => NbtTag::ByteArray(items.iter().map(|v| v.as_number() as i8).collect()),
+            3 => NbtTag::IntArray(items.iter().map(|v| v.as_number() as i32).collect()),
+            4 => NbtTag::LongArray(
+                items
+                    .into_iter()
+                    .map(|v| match v {
+                        NbtTag::Long(v) => v,
+                        _ => 0,
+                    })
+                    .collect(),
+            ),
+            _ => NbtTag::List { tag_type, items },
+        };
+    }
+
+    let items = items
+        .into_iter()
+        .map(|item| match item {
+            NbtTag::Compound(map) if !is_wrapper(&map) => NbtTag::Compound(map),
+            item => NbtTag::Compound(CompoundMap::from([(String::new(), item)])),
+        })
+        .collect();
+    NbtTag::List {
+        tag_type: 10,
+        items,
+    }
+}
+
+fn typed_name(tag_type: u8) -> &'static str {
+    match tag_type {
+        0 => "end",
+        1 => "byte",
+        2 => "short",
+        3 => "int",
+        4 => "long",
+        5 => "float",
+        6 => "double",
+        7 => "byte_array",
+        8 => "string",
+        9 => "list",
+        10 => "compound",
+        11 => "int_array",
+        _ => "long_array",
+    }
+}
+
+fn typed_id(name: &str) -> Option<u8> {
+    (0..=12).find(|&id| typed_name(id) == name)
+}
+
+fn typed_float(value: f64) -> Value {
+    if value.is_finite() {
+        float_to_json(value)
+    } else {
+        // JSON has no NaN or infinities, keep them as strings
+        Value::String(value.to_string())
+    }
+}
+
+fn typed_to_json(tag: &NbtTag) -> Value {
+    let mut object = Map::new();
+    object.insert("type".into(), typed_name(tag.type_id()).into());
+
+    let value = match tag {
+        NbtTag::End => return Value::Object(object),
+        NbtTag::Byte(v) => Value::from(*v),
+        NbtTag::Short(v) => Value::from(*v),
+        NbtTag::Int(v) => Value::from(*v),
+        NbtTag::Long(v) => Value::from(*v),
+        NbtTag::Float(v) => typed_float(v.to_string().parse().unwrap_or(*v as f64)),
+        NbtTag::Double(v) => typed_float(*v),
+        NbtTag::ByteArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+        NbtTag::String(s) => Value::String(s.clone()),
+        NbtTag::List { tag_type, items } => {
+            object.insert("element_type".into(), typed_name(*tag_type).into());
+            items.iter().map(typed_to_json).collect()
+        }
+        NbtTag::Compound(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), typed_to_json(value)))
+                .collect(),
+        ),
+        NbtTag::IntArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+        NbtTag::LongArray(array) => array.iter().map(|v| Value::from(*v)).collect(),
+    };
+
+    object.insert("value".into(), value);
+    Value::Object(object)
+}
+
+fn json_error(message: impl Into<String>) -> NbtError {
+    NbtError::Json(message.into())
+}
+
+fn typed_from_json(value: &Value) -> Result<NbtTag> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| json_error(format!("Expected typed object, found {value}")))?;
+    let type_name = object
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| json_error("Missing \"type\" field"))?;
+    let tag_type =
+        typed_id(type_name).ok_or_else(|| json_error(format!("Unknown type \"{type_name}\"")))?;
+
+    if tag_type == 0 {
+        return Ok(NbtTag::End);
+    }
+    let value = object
+        .get("value")
+        .ok_or_else(|| json_error(format!("Missing \"value\" field for {type_name}")))?;
+
+    Ok(match tag_type {
+        1 => NbtTag::Byte(typed_integer(value, "byte")?),
+        2 => NbtTag::Short(typed_integer(value, "short")?),
+        3 => NbtTag::Int(typed_integer(value, "int")?),
+        4 => NbtTag::Long(typed_integer(value, "long")?),
+        5 => NbtTag::Float(typed_decimal(value)? as f32),
+        6 => NbtTag::Double(typed_decimal(value)?),
+        7 => NbtTag::ByteArray(
+            typed_array(value)?
+                .iter()
+                .map(|v| typed_integer(v, "byte"))
+                .collect::<Result<_>>()?,
+        ),
+        8 => NbtTag::String(
+            value
+                .as_str()
+                .ok_or_else(|| json_error(format!("Expected string, found {value}")))?
+                .to_string(),
+        ),
+        9 => {
+            let element_type = object
+                .get("element_type")
+                .and_then(Value::as_str)
+                .and_then(typed_id)
+                .ok_or_else(|| json_error("Missing or unknown \"element_type\" field"))?;
+            let items = typed_array(value)?
+                .iter()
+                .map(typed_from_json)
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(item) = items.iter().find(|item| item.type_id() != element_type) {
+                return Err(NbtError::ListTypeMismatch {
+                    expected: element_type,
+                    found: item.type_id(),
+                });
+            }
+            NbtTag::List {
+                tag_type: element_type,
+                items,
+            }
+        }
+        10 => NbtTag::Compound(
+            value
+                .as_object()
+                .ok_or_else(|| json_error(format!("Expected object, found {value}")))?
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), typed_from_json(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+        11 => NbtTag::IntArray(
+            typed_array(value)?
+                .iter()
+                .map(|v| typed_integer(v, "int"))
+                .collect::<Result<_>>()?,
+        ),
+        _ => NbtTag::LongArray(
+            typed_array(value)?
+                .iter()
+                .map(|v| typed_integer(v, "long"))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+// Integers out of the range of the tag type are rejected instead of wrapped
+fn typed_integer<T: TryFrom<i64>>(value: &Value, type_name: &str) -> Result<T> {
+    let integer = value
+        .as_i64()
+        .ok_or_else(|| json_error(format!("Expected integer, found {value}")))?;
+    T::try_from(integer)
+        .map_err(|_| json_error(format!("{integer} is out of range for {type_name}")))
+}
+
+fn typed_decimal(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| json_error(format!("Expected number, found {value}")))
+}
+
+fn typed_array(value: &Value) -> Result<&Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| json_error(format!("Expected array, found {value}")))
+}
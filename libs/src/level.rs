@@ -0,0 +1,467 @@
+use crate::{CompoundMap, NbtError, NbtFile, NbtTag, Result};
+use std::fmt;
+use std::path::Path;
+
+// Vanilla game rules by value type; other rules are checked against their current value
+const BOOLEAN_RULES: &[&str] = &[
+    "announceAdvancements",
+    "blockExplosionDropDecay",
+    "commandBlockOutput",
+    "disableElytraMovementCheck",
+    "disableRaids",
+    "doDaylightCycle",
+    "doEntityDrops",
+    "doFireTick",
+    "doImmediateRespawn",
+    "doInsomnia",
+    "doLimitedCrafting",
+    "doMobLoot",
+    "doMobSpawning",
+    "doPatrolSpawning",
+    "doTileDrops",
+    "doTraderSpawning",
+    "doVinesSpread",
+    "doWardenSpawning",
+    "doWeatherCycle",
+    "drowningDamage",
+    "enderPearlsVanishOnDeath",
+    "fallDamage",
+    "fireDamage",
+    "forgiveDeadPlayers",
+    "freezeDamage",
+    "globalSoundEvents",
+    "keepInventory",
+    "lavaSourceConversion",
+    "logAdminCommands",
+    "mobExplosionDropDecay",
+    "mobGriefing",
+    "naturalRegeneration",
+    "reducedDebugInfo",
+    "sendCommandFeedback",
+    "showDeathMessages",
+    "spectatorsGenerateChunks",
+    "tntExplosionDropDecay",
+    "universalAnger",
+    "waterSourceConversion",
+];
+
+const INTEGER_RULES: &[&str] = &[
+    "commandModificationBlockLimit",
+    "maxCommandChainLength",
+    "maxCommandForkCount",
+    "maxEntityCramming",
+    "playersNetherPortalCreativeDelay",
+    "playersNetherPortalDefaultDelay",
+    "playersSleepingPercentage",
+    "randomTickSpeed",
+    "snowAccumulationHeight",
+    "spawnChunkRadius",
+    "spawnRadius",
+];
+
+/// Game rule value; `level.dat` stores every rule as a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl GameRuleValue {
+    /// Parse a stored rule (`"true"`, `"false"` or an integer)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "true" => Some(Self::Bool(true)),
+            "false" => Some(Self::Bool(false)),
+            _ => value.parse().ok().map(Self::Int),
+        }
+    }
+
+    fn type_name(self) -> &'static str {
+        match self {
+            Self::Bool(_) => "boolean",
+            Self::Int(_) => "integer",
+        }
+    }
+}
+
+impl fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<bool> for GameRuleValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for GameRuleValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// World border settings, stored as the `Border*` doubles of `level.dat`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    pub size: f64,
+    pub safe_zone: f64,
+    pub damage_per_block: f64,
+    pub warning_blocks: f64,
+    pub warning_time: f64,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center_x: 0.0,
+            center_z: 0.0,
+            size: 59_999_968.0,
+            safe_zone: 5.0,
+            damage_per_block: 0.2,
+            warning_blocks: 5.0,
+            warning_time: 15.0,
+        }
+    }
+}
+
+impl WorldBorder {
+    const KEYS: [&'static str; 7] = [
+        "BorderCenterX",
+        "BorderCenterZ",
+        "BorderSize",
+        "BorderSafeZone",
+        "BorderDamagePerBlock",
+        "BorderWarningBlocks",
+        "BorderWarningTime",
+    ];
+
+    fn fields_mut(&mut self) -> [&mut f64; 7] {
+        [
+            &mut self.center_x,
+            &mut self.center_z,
+            &mut self.size,
+            &mut self.safe_zone,
+            &mut self.damage_per_block,
+            &mut self.warning_blocks,
+            &mut self.warning_time,
+        ]
+    }
+}
+
+/// Typed editor for Java Edition `level.dat` files
+///
+/// Accessors read and write the `Data` compound in place, so unknown keys are kept as they
+/// are. `write` and `save` use the compression and root name the file was read with.
+/// Getters fail with the key path (`Data.SpawnX: missing key`) when a field is missing or
+/// has another type.
+#[derive(Debug, Clone)]
+pub struct LevelData {
+    file: NbtFile,
+}
+
+impl LevelData {
+    pub fn read(data: &[u8]) -> Result<Self> {
+        Self::from_file(NbtFile::read(data, None)?)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file(NbtFile::from_path(path)?)
+    }
+
+    /// Wrap a parsed file, which must have a `Data` compound
+    pub fn from_file(file: NbtFile) -> Result<Self> {
+        file.root.try_get_compound("Data")?;
+        Ok(Self { file })
+    }
+
+    pub fn write(&self) -> Result<Vec<u8>> {
+        self.file.write()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.file.save(path)
+    }
+
+    pub fn file(&self) -> &NbtFile {
+        &self.file
+    }
+
+    pub fn into_file(self) -> NbtFile {
+        self.file
+    }
+
+    /// The `Data` compound
+    pub fn data(&self) -> &NbtTag {
+        self.file.root.get("Data").expect("checked by from_file")
+    }
+
+    pub fn data_mut(&mut self) -> &mut CompoundMap {
+        // `Data` can't be replaced through this type, so it stays a compound
+        self.file
+            .root
+            .get_mut("Data")
+            .and_then(NbtTag::as_compound_mut)
+            .expect("checked by from_file")
+    }
+
+    pub fn level_name(&self) -> Result<&str> {
+        in_data(self.data().try_get_str("LevelName"))
+    }
+
+    pub fn set_level_name(&mut self, name: impl Into<String>) {
+        self.set("LevelName", NbtTag::String(name.into()));
+    }
+
+    pub fn data_version(&self) -> Result<i32> {
+        in_data(self.data().try_get_i32("DataVersion"))
+    }
+
+    /// Set `DataVersion`, and `Version.Id` when the file has a `Version` compound
+    pub fn set_data_version(&mut self, version: i32) {
+        self.set("DataVersion", NbtTag::Int(version));
+        if let Some(NbtTag::Compound(info)) = self.data_mut().get_mut("Version") {
+            info.insert("Id".to_string(), NbtTag::Int(version));
+        }
+    }
+
+    /// Game version name from `Version.Name` (e.g. `1.20.4`)
+    pub fn version_name(&self) -> Result<&str> {
+        in_data(
+            self.data()
+                .try_get("Version")
+                .and_then(|info| info.try_get_str("Name").map_err(|e| e.at_key("Version"))),
+        )
+    }
+
+    pub fn game_type(&self) -> Result<i32> {
+        in_data(self.data().try_get_i32("GameType"))
+    }
+
+    pub fn set_game_type(&mut self, game_type: i32) {
+        self.set("GameType", NbtTag::Int(game_type));
+    }
+
+    pub fn difficulty(&self) -> Result<i8> {
+        in_data(self.data().try_get_i8("Difficulty"))
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: i8) {
+        self.set("Difficulty", NbtTag::Byte(difficulty));
+    }
+
+    pub fn hardcore(&self) -> Result<bool> {
+        in_data(self.data().try_get_bool("hardcore"))
+    }
+
+    pub fn set_hardcore(&mut self, hardcore: bool) {
+        self.set("hardcore", NbtTag::from(hardcore));
+    }
+
+    pub fn allow_commands(&self) -> Result<bool> {
+        in_data(self.data().try_get_bool("allowCommands"))
+    }
+
+    pub fn set_allow_commands(&mut self, allow: bool) {
+        self.set("allowCommands", NbtTag::from(allow));
+    }
+
+    /// Total game time in ticks
+    pub fn time(&self) -> Result<i64> {
+        in_data(self.data().try_get_i64("Time"))
+    }
+
+    /// Time of day in ticks
+    pub fn day_time(&self) -> Result<i64> {
+        in_data(self.data().try_get_i64("DayTime"))
+    }
+
+    pub fn set_day_time(&mut self, ticks: i64) {
+        self.set("DayTime", NbtTag::Long(ticks));
+    }
+
+    /// World spawn from `SpawnX`, `SpawnY` and `SpawnZ`
+    pub fn spawn(&self) -> Result<(i32, i32, i32)> {
+        let coordinate = |key| in_data(self.data().try_get_i32(key));
+        Ok((
+            coordinate("SpawnX")?,
+            coordinate("SpawnY")?,
+            coordinate("SpawnZ")?,
+        ))
+    }
+
+    pub fn set_spawn(&mut self, x: i32, y: i32, z: i32) {
+        self.set("SpawnX", NbtTag::Int(x));
+        self.set("SpawnY", NbtTag::Int(y));
+        self.set("SpawnZ", NbtTag::Int(z));
+    }
+
+    /// World border, with vanilla defaults for missing fields
+    pub fn world_border(&self) -> Result<WorldBorder> {
+        let mut border = WorldBorder::default();
+        for (key, field) in WorldBorder::KEYS.iter().zip(border.fields_mut()) {
+            if self.data().get(key).is_some() {
+                *field = in_data(self.data().try_get_f64(key))?;
+            }
+        }
+        Ok(border)
+    }
+
+    pub fn set_world_border(&mut self, border: &WorldBorder) {
+        let mut border = *border;
+        for (key, field) in WorldBorder::KEYS.iter().zip(border.fields_mut()) {
+            self.set(key, NbtTag::Double(*field));
+        }
+    }
+
+    /// Data packs listed in `DataPacks.Enabled`
+    pub fn enabled_data_packs(&self) -> Result<Vec<String>> {
+        self.data_packs("Enabled")
+    }
+
+    /// Data packs listed in `DataPacks.Disabled`
+    pub fn disabled_data_packs(&self) -> Result<Vec<String>> {
+        self.data_packs("Disabled")
+    }
+
+    /// Move a data pack to the enabled list (returns whether anything changed)
+    pub fn enable_data_pack(&mut self, name: &str) -> Result<bool> {
+        self.move_data_pack(name, "Disabled", "Enabled")
+    }
+
+    /// Move a data pack to the disabled list (returns whether anything changed)
+    pub fn disable_data_pack(&mut self, name: &str) -> Result<bool> {
+        self.move_data_pack(name, "Enabled", "Disabled")
+    }
+
+    /// `WorldGenSettings` compound (dimensions, seed, structure generation)
+    pub fn world_gen_settings(&self) -> Result<&CompoundMap> {
+        in_data(self.data().try_get_compound("WorldGenSettings"))
+    }
+
+    pub fn world_gen_settings_mut(&mut self) -> Result<&mut CompoundMap> {
+        let data = self.file.root.try_get_mut("Data")?;
+        in_data(data.try_get_compound_mut("WorldGenSettings"))
+    }
+
+    /// World seed from `WorldGenSettings.seed`, or `RandomSeed` before 1.16
+    pub fn seed(&self) -> Result<i64> {
+        match self.data().get("WorldGenSettings") {
+            Some(settings) => in_data(
+                settings
+                    .try_get_i64("seed")
+                    .map_err(|e| e.at_key("WorldGenSettings")),
+            ),
+            None => in_data(self.data().try_get_i64("RandomSeed")),
+        }
+    }
+
+    /// Raw game rule string from `GameRules`
+    pub fn game_rule(&self, name: &str) -> Option<&str> {
+        match self.data().get_compound("GameRules")?.get(name)? {
+            NbtTag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn game_rule_value(&self, name: &str) -> Option<GameRuleValue> {
+        GameRuleValue::parse(self.game_rule(name)?)
+    }
+
+    /// Set a game rule, checking the value type of vanilla rules and of rules already set
+    pub fn set_game_rule(&mut self, name: &str, value: impl Into<GameRuleValue>) -> Result<()> {
+        let value = value.into();
+        let expected = if BOOLEAN_RULES.contains(&name) {
+            Some(GameRuleValue::Bool(false))
+        } else if INTEGER_RULES.contains(&name) {
+            Some(GameRuleValue::Int(0))
+        } else {
+            self.game_rule_value(name)
+        };
+        if let Some(expected) = expected {
+            if std::mem::discriminant(&expected) != std::mem::discriminant(&value) {
+                return Err(NbtError::TypeMismatch {
+                    path: String::new(),
+                    expected: expected.type_name().to_string(),
+                    found: value.type_name().to_string(),
+                }
+                .at_key(name)
+                .at_key("GameRules")
+                .at_key("Data"));
+            }
+        }
+
+        let rules = self
+            .data_mut()
+            .entry("GameRules".to_string())
+            .or_insert_with(NbtTag::compound);
+        let found = rules.type_name();
+        let Some(rules) = rules.as_compound_mut() else {
+            return Err(NbtError::type_mismatch("Compound", found)
+                .at_key("GameRules")
+                .at_key("Data"));
+        };
+        rules.insert(name.to_string(), NbtTag::String(value.to_string()));
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: NbtTag) {
+        self.data_mut().insert(key.to_string(), value);
+    }
+
+    fn data_packs(&self, list: &str) -> Result<Vec<String>> {
+        match self.data().get("DataPacks") {
+            Some(packs) if packs.get(list).is_some() => in_data(
+                packs
+                    .try_get_list_of(list)
+                    .map_err(|e| e.at_key("DataPacks")),
+            ),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn move_data_pack(&mut self, name: &str, from: &str, to: &str) -> Result<bool> {
+        let packs = self
+            .data_mut()
+            .entry("DataPacks".to_string())
+            .or_insert_with(NbtTag::compound);
+        let found = packs.type_name();
+        let Some(packs) = packs.as_compound_mut() else {
+            return Err(NbtError::type_mismatch("Compound", found)
+                .at_key("DataPacks")
+                .at_key("Data"));
+        };
+        let mut changed = false;
+        if let Some(NbtTag::List { tag_type, items }) = packs.get_mut(from) {
+            let before = items.len();
+            items.retain(|item| item.as_string() != name);
+            if items.is_empty() {
+                *tag_type = 0;
+            }
+            changed = items.len() != before;
+        }
+        let target = packs
+            .entry(to.to_string())
+            .or_insert_with(|| NbtTag::list(8));
+        let present = target
+            .as_list()
+            .is_some_and(|(_, items)| items.iter().any(|item| item.as_string() == name));
+        if !present {
+            target
+                .push(NbtTag::String(name.to_string()))
+                .map_err(|e| e.at_key(to).at_key("DataPacks").at_key("Data"))?;
+            changed = true;
+        }
+        Ok(changed)
+    }
+}
+
+fn in_data<T>(result: Result<T>) -> Result<T> {
+    result.map_err(|e| e.at_key("Data"))
+}
@@ -1,22 +1,78 @@
+extern crate self as nbt;
+
+mod builder;
+mod canonical;
+mod convert;
+mod diff;
 mod error;
+mod events;
+mod item;
+mod level;
+mod matches;
+mod merge;
 mod reader;
 mod tag;
+mod uuids;
+mod visit;
 
 pub mod compression;
 
+mod path;
+mod player;
 mod snbt;
+mod stream_writer;
+mod tag_ref;
+mod snbt_lexer;
 
 mod region;
 
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "serde")]
+mod serde_helpers;
+
+pub use builder::*;
+pub use canonical::*;
+pub use convert::*;
+pub use diff::*;
 pub use error::*;
+pub use events::*;
+pub use item::*;
+pub use level::*;
+pub use merge::*;
 pub use reader::*;
 pub use tag::*;
+pub use uuids::*;
+pub use visit::*;
 pub mod wasm;
 
-pub use std::collections::HashMap;
-
 pub use compression::*;
 
+#[cfg(feature = "derive")]
+pub use nbt_macros::NbtCompound;
+#[cfg(feature = "macros")]
+pub use nbt_macros::nbt;
+
+pub use path::*;
+pub use player::*;
 pub use snbt::*;
+pub use stream_writer::*;
+pub use tag_ref::*;
+pub use snbt_lexer::*;
 
 pub use region::*;
+
+#[cfg(feature = "json")]
+pub use json::*;
+
+#[cfg(feature = "serde")]
+pub use de::*;
+#[cfg(feature = "serde")]
+pub use ser::*;
+#[cfg(feature = "serde")]
+pub use serde_helpers::*;
@@ -0,0 +1,41 @@
+use crate::{CompoundMap, NbtTag};
+
+impl NbtTag {
+    // Vanilla NbtUtils.compareNbt: compound entries of the pattern must match, other keys are
+    // ignored. With `partial_lists`, every pattern list element must match some element and an
+    // empty pattern list only matches an empty list; otherwise lists must be equal
+    pub fn matches(&self, pattern: &NbtTag, partial_lists: bool) -> bool {
+        matches_tag(pattern, self, partial_lists)
+    }
+}
+
+fn matches_tag(pattern: &NbtTag, tag: &NbtTag, partial_lists: bool) -> bool {
+    match (pattern, tag) {
+        (NbtTag::Compound(pattern), NbtTag::Compound(map)) => {
+            matches_compound(pattern, map, partial_lists)
+        }
+        (NbtTag::List { items: pattern, .. }, NbtTag::List { items, .. }) => {
+            if !partial_lists {
+                return pattern == items;
+            }
+            if pattern.is_empty() {
+                return items.is_empty();
+            }
+            pattern
+                .iter()
+                .all(|p| items.iter().any(|item| matches_tag(p, item, partial_lists)))
+        }
+        _ => pattern == tag,
+    }
+}
+
+pub(crate) fn matches_compound(
+    pattern: &CompoundMap,
+    map: &CompoundMap,
+    partial_lists: bool,
+) -> bool {
+    pattern.iter().all(|(key, expected)| {
+        map.get(key)
+            .is_some_and(|value| matches_tag(expected, value, partial_lists))
+    })
+}
@@ -0,0 +1,109 @@
+use crate::{CompoundMap, NbtTag};
+
+/// How `NbtTag::merge_with` combines a list in the source with a list in the target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ListMerge {
+    /// Source list replaces the target list, like `/data merge`
+    #[default]
+    Replace,
+    /// Source elements are appended when both lists have the same element type
+    Append,
+    /// Compound elements with equal values for the key (`Slot`, `id`, ...) are merged,
+    /// the others are appended; lists of other element types are replaced
+    ByKey(String),
+}
+
+impl NbtTag {
+    // Merge like `/data merge` / CompoundTag.merge: compounds recursively, anything else replaced.
+    // Returns whether self changed
+    pub fn merge(&mut self, source: &NbtTag) -> bool {
+        self.merge_with(source, &ListMerge::Replace)
+    }
+
+    // Merge with a list strategy (returns whether self changed)
+    pub fn merge_with(&mut self, source: &NbtTag, lists: &ListMerge) -> bool {
+        merge_tag(self, source, lists)
+    }
+}
+
+// Deep merge of `source` into `target`, returns whether anything changed
+pub(crate) fn merge_compound(
+    target: &mut CompoundMap,
+    source: &CompoundMap,
+    lists: &ListMerge,
+) -> bool {
+    let mut changed = false;
+    for (key, value) in source {
+        match target.get_mut(key) {
+            Some(existing) => changed |= merge_tag(existing, value, lists),
+            None => {
+                target.insert(key.clone(), value.clone());
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn merge_tag(target: &mut NbtTag, source: &NbtTag, lists: &ListMerge) -> bool {
+    match (target, source, lists) {
+        (NbtTag::Compound(existing), NbtTag::Compound(nested), _) => {
+            merge_compound(existing, nested, lists)
+        }
+        (
+            NbtTag::List { .. },
+            NbtTag::List { items, .. },
+            ListMerge::Append | ListMerge::ByKey(_),
+        ) if items.is_empty() => false,
+        (
+            NbtTag::List { tag_type, items },
+            NbtTag::List {
+                tag_type: source_type,
+                items: source_items,
+            },
+            ListMerge::Append | ListMerge::ByKey(_),
+        ) if items.is_empty() || *tag_type == *source_type => {
+            *tag_type = *source_type;
+            match lists {
+                ListMerge::ByKey(key) if *source_type == 10 => {
+                    merge_by_key(items, source_items, key, lists)
+                }
+                ListMerge::ByKey(_) => replace(items, source_items),
+                _ => {
+                    items.extend_from_slice(source_items);
+                    true
+                }
+            }
+        }
+        (target, source, _) if *target == *source => false,
+        (target, source, _) => {
+            *target = source.clone();
+            true
+        }
+    }
+}
+
+fn merge_by_key(items: &mut Vec<NbtTag>, source: &[NbtTag], key: &str, lists: &ListMerge) -> bool {
+    let mut changed = false;
+    for element in source {
+        let existing = element
+            .get(key)
+            .and_then(|id| items.iter_mut().find(|item| item.get(key) == Some(id)));
+        match existing {
+            Some(existing) => changed |= merge_tag(existing, element, lists),
+            None => {
+                items.push(element.clone());
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn replace(items: &mut Vec<NbtTag>, source: &[NbtTag]) -> bool {
+    if items.as_slice() == source {
+        return false;
+    }
+    *items = source.to_vec();
+    true
+}
@@ -0,0 +1,794 @@
+use crate::matches::matches_compound;
+use crate::merge::merge_compound;
+use crate::{
+    format_snbt, parse_snbt_streaming, CompoundMap, ListMerge, NbtError, NbtTag, Result,
+};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// One step of an NBT path
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathNode {
+    /// `name` or `"quoted name"`
+    Child(String),
+    /// `name{pattern}`: the child, if it matches the pattern
+    MatchObject(String, CompoundMap),
+    /// `{pattern}` at the start of the path: the root, if it matches
+    MatchRoot(CompoundMap),
+    /// `[index]`, negative indices count from the end
+    Index(i32),
+    /// `[]`: every element of a list or array
+    All,
+    /// `[{pattern}]`: every list element matching the pattern
+    MatchElement(CompoundMap),
+}
+
+/// NBT path with the vanilla `/data` syntax (`a.b[0].c`, `Items[{id:"minecraft:stone"}]`, `[]`, ...)
+///
+/// Operations follow `/data get` and `/data modify`: missing parents are created by `set`,
+/// `insert` and `merge`, and mutations return the number of changed elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    nodes: Vec<PathNode>,
+}
+
+impl NbtPath {
+    pub fn new(nodes: Vec<PathNode>) -> Self {
+        Self { nodes }
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        PathParser { input, pos: 0 }.parse()
+    }
+
+    pub fn nodes(&self) -> &[PathNode] {
+        &self.nodes
+    }
+
+    pub fn push(&mut self, node: PathNode) {
+        self.nodes.push(node);
+    }
+
+    pub fn pop(&mut self) -> Option<PathNode> {
+        self.nodes.pop()
+    }
+
+    /// Path with `node` appended
+    pub fn join(&self, node: PathNode) -> Self {
+        let mut path = self.clone();
+        path.push(node);
+        path
+    }
+
+    /// First element matched by the path
+    pub fn get<'a>(&self, root: &'a NbtTag) -> Option<Cow<'a, NbtTag>> {
+        self.get_all(root).into_iter().next()
+    }
+
+    /// All elements matched by the path (array elements are returned as owned tags)
+    pub fn get_all<'a>(&self, root: &'a NbtTag) -> Vec<Cow<'a, NbtTag>> {
+        get_all_nodes(&self.nodes, root)
+    }
+
+    pub fn count(&self, root: &NbtTag) -> usize {
+        self.get_all(root).len()
+    }
+
+    /// Mutable references to the matched compounds and lists (array elements are skipped)
+    pub fn get_all_mut<'a>(&self, root: &'a mut NbtTag) -> Vec<&'a mut NbtTag> {
+        resolve_mut(&self.nodes, root)
+    }
+
+    /// `/data modify <path> set value`, creating missing parents
+    pub fn set(&self, root: &mut NbtTag, value: NbtTag) -> usize {
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return 0;
+        };
+        get_or_create_parents(parents, last, root)
+            .into_iter()
+            .map(|parent| set_node(last, parent, &value))
+            .sum()
+    }
+
+    /// `/data remove <path>`
+    pub fn remove(&self, root: &mut NbtTag) -> usize {
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return 0;
+        };
+        resolve_mut(parents, root)
+            .into_iter()
+            .map(|parent| remove_node(last, parent))
+            .sum()
+    }
+
+    /// `/data modify <path> insert <index>`, negative indices count from the end (`-1` appends)
+    pub fn insert(&self, root: &mut NbtTag, index: i32, values: &[NbtTag]) -> Result<usize> {
+        let mut count = 0;
+        for target in self.get_or_create(root, || NbtTag::list(0)) {
+            let Some(len) = collection_len(target) else {
+                return Err(NbtError::TypeMismatch {
+                    path: self.to_string(),
+                    expected: "List".to_string(),
+                    found: target.type_name().to_string(),
+                });
+            };
+            let mut position = if index < 0 {
+                len as i64 + index as i64 + 1
+            } else {
+                index as i64
+            };
+            let mut changed = false;
+            for value in values {
+                let len = collection_len(target).unwrap_or(0);
+                if position < 0 || position > len as i64 {
+                    return Err(NbtError::IndexOutOfBounds {
+                        index: position,
+                        len,
+                    });
+                }
+                if add_element(target, position as usize, value.clone()) {
+                    position += 1;
+                    changed = true;
+                }
+            }
+            count += changed as usize;
+        }
+        Ok(count)
+    }
+
+    /// `/data modify <path> append`
+    pub fn append(&self, root: &mut NbtTag, value: NbtTag) -> Result<usize> {
+        self.insert(root, -1, &[value])
+    }
+
+    /// `/data modify <path> prepend`
+    pub fn prepend(&self, root: &mut NbtTag, value: NbtTag) -> Result<usize> {
+        self.insert(root, 0, &[value])
+    }
+
+    /// `/data modify <path> merge`, deep-merging `source` into every matched compound
+    pub fn merge(&self, root: &mut NbtTag, source: &CompoundMap) -> Result<usize> {
+        let mut count = 0;
+        for target in self.get_or_create(root, NbtTag::compound) {
+            match target {
+                NbtTag::Compound(map) => count += merge_compound(map, source, &ListMerge::Replace) as usize,
+                other => {
+                    return Err(NbtError::TypeMismatch {
+                        path: self.to_string(),
+                        expected: "Compound".to_string(),
+                        found: other.type_name().to_string(),
+                    })
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    fn get_or_create<'a>(
+        &self,
+        root: &'a mut NbtTag,
+        create: fn() -> NbtTag,
+    ) -> Vec<&'a mut NbtTag> {
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return vec![root];
+        };
+        let mut out = Vec::new();
+        for parent in get_or_create_parents(parents, last, root) {
+            get_or_create_node(last, parent, create, &mut out);
+        }
+        out
+    }
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node {
+                PathNode::Child(name) | PathNode::MatchObject(name, _) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(&format_key(name))?;
+                    if let PathNode::MatchObject(_, pattern) = node {
+                        f.write_str(&format_pattern(pattern))?;
+                    }
+                }
+                PathNode::MatchRoot(pattern) => f.write_str(&format_pattern(pattern))?,
+                PathNode::Index(index) => write!(f, "[{index}]")?,
+                PathNode::All => f.write_str("[]")?,
+                PathNode::MatchElement(pattern) => write!(f, "[{}]", format_pattern(pattern))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NbtTag {
+    // Get first element at an NBT path
+    pub fn get_path(&self, path: &NbtPath) -> Option<Cow<'_, NbtTag>> {
+        path.get(self)
+    }
+
+    // Get all elements at an NBT path
+    pub fn get_path_all(&self, path: &NbtPath) -> Vec<Cow<'_, NbtTag>> {
+        path.get_all(self)
+    }
+
+    // Set value at an NBT path (returns changed count)
+    pub fn set_path(&mut self, path: &NbtPath, value: NbtTag) -> usize {
+        path.set(self, value)
+    }
+
+    // Remove elements at an NBT path (returns removed count)
+    pub fn remove_path(&mut self, path: &NbtPath) -> usize {
+        path.remove(self)
+    }
+
+    // Insert values in the lists at an NBT path
+    pub fn insert_path(&mut self, path: &NbtPath, index: i32, values: &[NbtTag]) -> Result<usize> {
+        path.insert(self, index, values)
+    }
+
+    // Append a value to the lists at an NBT path
+    pub fn append_path(&mut self, path: &NbtPath, value: NbtTag) -> Result<usize> {
+        path.append(self, value)
+    }
+
+    // Prepend a value to the lists at an NBT path
+    pub fn prepend_path(&mut self, path: &NbtPath, value: NbtTag) -> Result<usize> {
+        path.prepend(self, value)
+    }
+
+    // Merge a compound into the compounds at an NBT path
+    pub fn merge_path(&mut self, path: &NbtPath, source: &CompoundMap) -> Result<usize> {
+        path.merge(self, source)
+    }
+}
+
+struct PathParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn parse(mut self) -> Result<NbtPath> {
+        let mut nodes = Vec::new();
+        while self.pos < self.input.len() {
+            nodes.push(self.parse_node(nodes.is_empty())?);
+            match self.peek() {
+                None | Some('[') | Some('{') => {}
+                Some('.') => {
+                    self.pos += 1;
+                    if self.pos == self.input.len() {
+                        return Err(self.error("expected key after '.'"));
+                    }
+                }
+                Some(c) => return Err(self.error(format!("unexpected character '{c}'"))),
+            }
+        }
+        if nodes.is_empty() {
+            return Err(self.error("empty path"));
+        }
+        Ok(NbtPath { nodes })
+    }
+
+    fn parse_node(&mut self, root: bool) -> Result<PathNode> {
+        match self.peek() {
+            Some('{') => {
+                if !root {
+                    return Err(self.error("compound pattern is only allowed at the root"));
+                }
+                Ok(PathNode::MatchRoot(self.parse_pattern()?))
+            }
+            Some('[') => {
+                self.pos += 1;
+                let node = match self.peek() {
+                    Some('{') => PathNode::MatchElement(self.parse_pattern()?),
+                    Some(']') => PathNode::All,
+                    _ => PathNode::Index(self.parse_index()?),
+                };
+                self.expect(']')?;
+                Ok(node)
+            }
+            Some(quote @ ('"' | '\'')) => {
+                let name = self.parse_quoted(quote)?;
+                self.parse_object_node(name)
+            }
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if " \"'[].{}".contains(c) {
+                        break;
+                    }
+                    self.pos += c.len_utf8();
+                }
+                if self.pos == start {
+                    return Err(self.error("expected key"));
+                }
+                let name = self.input[start..self.pos].to_string();
+                self.parse_object_node(name)
+            }
+        }
+    }
+
+    fn parse_object_node(&mut self, name: String) -> Result<PathNode> {
+        if self.peek() == Some('{') {
+            Ok(PathNode::MatchObject(name, self.parse_pattern()?))
+        } else {
+            Ok(PathNode::Child(name))
+        }
+    }
+
+    fn parse_index(&mut self) -> Result<i32> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map_err(|_| NbtError::InvalidPath {
+                message: "expected index".to_string(),
+                position: start,
+            })
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut name = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) if escaped == '\\' || escaped == quote => name.push(escaped),
+                    _ => {
+                        return Err(self.error_at("invalid escape in quoted key", self.pos + offset))
+                    }
+                },
+                c if c == quote => {
+                    self.pos += offset + 1;
+                    return Ok(name);
+                }
+                c => name.push(c),
+            }
+        }
+        Err(self.error_at("unterminated quoted key", start))
+    }
+
+    fn parse_pattern(&mut self) -> Result<CompoundMap> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut quote = None;
+        let mut escaped = false;
+        for (offset, c) in self.input[start..].char_indices() {
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                '{' | '[' => depth += 1,
+                '}' | ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let end = start + offset + 1;
+                        let pattern =
+                            parse_snbt_streaming(&self.input[start..end]).map_err(|e| {
+                                self.error_at(format!("invalid compound pattern ({e})"), start)
+                            })?;
+                        self.pos = end;
+                        return match pattern {
+                            NbtTag::Compound(map) => Ok(map),
+                            _ => Err(self.error_at("expected compound pattern", start)),
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error_at("unterminated compound pattern", start))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{expected}'")))
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> NbtError {
+        self.error_at(message, self.pos)
+    }
+
+    fn error_at(&self, message: impl Into<String>, position: usize) -> NbtError {
+        NbtError::InvalidPath {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+fn format_key(name: &str) -> String {
+    if !name.is_empty() && !name.chars().any(|c| " \"'[].{}".contains(c)) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn format_pattern(pattern: &CompoundMap) -> String {
+    format_snbt(&NbtTag::Compound(pattern.clone()))
+}
+
+// Path patterns use vanilla partial list matching
+fn matches_tag(pattern: &CompoundMap, tag: &NbtTag) -> bool {
+    matches!(tag, NbtTag::Compound(map) if matches_compound(pattern, map, true))
+}
+
+pub(crate) fn get_all_nodes<'a>(nodes: &[PathNode], root: &'a NbtTag) -> Vec<Cow<'a, NbtTag>> {
+    let mut current = vec![Cow::Borrowed(root)];
+    for node in nodes {
+        let mut next = Vec::new();
+        for tag in &current {
+            if let Cow::Borrowed(tag) = tag {
+                get_node(node, tag, &mut next);
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+// Matches of `rest` below a child that `node` selected by key or index
+pub(crate) fn get_below<'a>(
+    node: &PathNode,
+    rest: &[PathNode],
+    child: &'a NbtTag,
+) -> Vec<Cow<'a, NbtTag>> {
+    match node {
+        PathNode::MatchObject(_, pattern) | PathNode::MatchElement(pattern)
+            if !matches_tag(pattern, child) =>
+        {
+            Vec::new()
+        }
+        _ => get_all_nodes(rest, child),
+    }
+}
+
+fn get_node<'a>(node: &PathNode, tag: &'a NbtTag, out: &mut Vec<Cow<'a, NbtTag>>) {
+    match (node, tag) {
+        (PathNode::Child(name), NbtTag::Compound(map)) => {
+            out.extend(map.get(name).map(Cow::Borrowed));
+        }
+        (PathNode::MatchObject(name, pattern), NbtTag::Compound(map)) => {
+            out.extend(
+                map.get(name)
+                    .filter(|child| matches_tag(pattern, child))
+                    .map(Cow::Borrowed),
+            );
+        }
+        (PathNode::MatchRoot(pattern), tag) if matches_tag(pattern, tag) => {
+            out.push(Cow::Borrowed(tag));
+        }
+        (PathNode::Index(index), tag) => {
+            if let Some(i) = collection_len(tag).and_then(|len| resolve_index(*index, len)) {
+                out.extend(element(tag, i));
+            }
+        }
+        (PathNode::All, tag) => {
+            let len = collection_len(tag).unwrap_or(0);
+            out.extend((0..len).filter_map(|i| element(tag, i)));
+        }
+        (PathNode::MatchElement(pattern), NbtTag::List { items, .. }) => {
+            out.extend(
+                items
+                    .iter()
+                    .filter(|item| matches_tag(pattern, item))
+                    .map(Cow::Borrowed),
+            );
+        }
+        _ => {}
+    }
+}
+
+fn get_mut_node<'a>(node: &PathNode, tag: &'a mut NbtTag, out: &mut Vec<&'a mut NbtTag>) {
+    match (node, tag) {
+        (PathNode::Child(name), NbtTag::Compound(map)) => out.extend(map.get_mut(name)),
+        (PathNode::MatchObject(name, pattern), NbtTag::Compound(map)) => {
+            out.extend(
+                map.get_mut(name)
+                    .filter(|child| matches_tag(pattern, child)),
+            );
+        }
+        (PathNode::MatchRoot(pattern), tag) if matches_tag(pattern, tag) => out.push(tag),
+        (PathNode::Index(index), NbtTag::List { items, .. }) => {
+            if let Some(i) = resolve_index(*index, items.len()) {
+                out.push(&mut items[i]);
+            }
+        }
+        (PathNode::All, NbtTag::List { items, .. }) => out.extend(items.iter_mut()),
+        (PathNode::MatchElement(pattern), NbtTag::List { items, .. }) => {
+            out.extend(items.iter_mut().filter(|item| matches_tag(pattern, item)));
+        }
+        _ => {}
+    }
+}
+
+fn get_or_create_node<'a>(
+    node: &PathNode,
+    tag: &'a mut NbtTag,
+    create: fn() -> NbtTag,
+    out: &mut Vec<&'a mut NbtTag>,
+) {
+    match (node, tag) {
+        (PathNode::Child(name), NbtTag::Compound(map)) => {
+            out.push(map.entry(name.clone()).or_insert_with(create));
+        }
+        (PathNode::MatchObject(name, pattern), NbtTag::Compound(map)) => {
+            let child = map
+                .entry(name.clone())
+                .or_insert_with(|| NbtTag::Compound(pattern.clone()));
+            if matches_tag(pattern, child) {
+                out.push(child);
+            }
+        }
+        (PathNode::All, tag @ NbtTag::List { .. }) => {
+            if collection_len(tag) == Some(0) {
+                add_element(tag, 0, create());
+            }
+            get_mut_node(node, tag, out);
+        }
+        (PathNode::MatchElement(pattern), tag @ NbtTag::List { .. }) => {
+            let NbtTag::List { items, .. } = &*tag else {
+                return;
+            };
+            if !items.iter().any(|item| matches_tag(pattern, item)) {
+                let len = items.len();
+                add_element(tag, len, NbtTag::Compound(pattern.clone()));
+            }
+            get_mut_node(node, tag, out);
+        }
+        (node, tag) => get_mut_node(node, tag, out),
+    }
+}
+
+fn resolve_mut<'a>(nodes: &[PathNode], root: &'a mut NbtTag) -> Vec<&'a mut NbtTag> {
+    let mut current = vec![root];
+    for node in nodes {
+        let mut next = Vec::new();
+        for tag in current {
+            get_mut_node(node, tag, &mut next);
+        }
+        current = next;
+    }
+    current
+}
+
+// Walk `parents`, creating each missing step with the container the following node expects
+fn get_or_create_parents<'a>(
+    parents: &[PathNode],
+    last: &PathNode,
+    root: &'a mut NbtTag,
+) -> Vec<&'a mut NbtTag> {
+    let mut current = vec![root];
+    for (i, node) in parents.iter().enumerate() {
+        let create = preferred_parent(parents.get(i + 1).unwrap_or(last));
+        let mut next = Vec::new();
+        for tag in current {
+            get_or_create_node(node, tag, create, &mut next);
+        }
+        current = next;
+    }
+    current
+}
+
+fn preferred_parent(node: &PathNode) -> fn() -> NbtTag {
+    match node {
+        PathNode::Index(_) | PathNode::All | PathNode::MatchElement(_) => || NbtTag::list(0),
+        _ => NbtTag::compound,
+    }
+}
+
+fn set_node(node: &PathNode, parent: &mut NbtTag, value: &NbtTag) -> usize {
+    match (node, parent) {
+        (PathNode::Child(name), NbtTag::Compound(map)) => {
+            let previous = map.insert(name.clone(), value.clone());
+            (previous.as_ref() != Some(value)) as usize
+        }
+        (PathNode::Index(index), parent) => {
+            let Some(i) = collection_len(parent).and_then(|len| resolve_index(*index, len)) else {
+                return 0;
+            };
+            let changed = element(parent, i).is_some_and(|old| *old != *value);
+            (changed && set_element(parent, i, value.clone())) as usize
+        }
+        (PathNode::All, parent) => {
+            let Some(len) = collection_len(parent) else {
+                return 0;
+            };
+            if len == 0 {
+                return add_element(parent, 0, value.clone()) as usize;
+            }
+            (0..len)
+                .filter(|&i| {
+                    element(parent, i).is_some_and(|old| *old != *value)
+                        && set_element(parent, i, value.clone())
+                })
+                .count()
+        }
+        (PathNode::MatchElement(pattern), parent @ NbtTag::List { .. }) => {
+            if parent.as_list().is_some_and(|(_, items)| items.is_empty()) {
+                return parent.push(value.clone()).is_ok() as usize;
+            }
+            let Some((tag_type, items)) = parent.as_list_mut() else {
+                return 0;
+            };
+            if value.type_id() != *tag_type {
+                return 0;
+            }
+            items
+                .iter_mut()
+                .filter(|item| matches_tag(pattern, item) && **item != *value)
+                .map(|item| *item = value.clone())
+                .count()
+        }
+        _ => 0,
+    }
+}
+
+fn remove_node(node: &PathNode, parent: &mut NbtTag) -> usize {
+    match (node, parent) {
+        (PathNode::Child(name), parent) => parent.remove(name).is_some() as usize,
+        (PathNode::MatchObject(name, pattern), parent)
+            if parent
+                .get(name)
+                .is_some_and(|child| matches_tag(pattern, child)) =>
+        {
+            parent.remove(name).is_some() as usize
+        }
+        (PathNode::Index(index), parent) => {
+            match collection_len(parent).and_then(|len| resolve_index(*index, len)) {
+                Some(i) => remove_element(parent, i) as usize,
+                None => 0,
+            }
+        }
+        (PathNode::All, parent) => {
+            let len = collection_len(parent).unwrap_or(0);
+            for i in (0..len).rev() {
+                remove_element(parent, i);
+            }
+            len
+        }
+        (PathNode::MatchElement(pattern), NbtTag::List { tag_type, items }) => {
+            let before = items.len();
+            items.retain(|item| !matches_tag(pattern, item));
+            if items.is_empty() {
+                *tag_type = 0;
+            }
+            before - items.len()
+        }
+        _ => 0,
+    }
+}
+
+pub(crate) fn resolve_index(index: i32, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        len as i64 + index as i64
+    } else {
+        index as i64
+    };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn collection_len(tag: &NbtTag) -> Option<usize> {
+    match tag {
+        NbtTag::List { items, .. } => Some(items.len()),
+        NbtTag::ByteArray(values) => Some(values.len()),
+        NbtTag::IntArray(values) => Some(values.len()),
+        NbtTag::LongArray(values) => Some(values.len()),
+        _ => None,
+    }
+}
+
+fn element(tag: &NbtTag, i: usize) -> Option<Cow<'_, NbtTag>> {
+    match tag {
+        NbtTag::List { items, .. } => items.get(i).map(Cow::Borrowed),
+        NbtTag::ByteArray(values) => values.get(i).map(|v| Cow::Owned(NbtTag::Byte(*v))),
+        NbtTag::IntArray(values) => values.get(i).map(|v| Cow::Owned(NbtTag::Int(*v))),
+        NbtTag::LongArray(values) => values.get(i).map(|v| Cow::Owned(NbtTag::Long(*v))),
+        _ => None,
+    }
+}
+
+// Numeric conversions of vanilla NumericTag.getAsInt / getAsLong
+fn numeric_as_i32(tag: &NbtTag) -> Option<i32> {
+    match tag {
+        NbtTag::Byte(v) => Some(*v as i32),
+        NbtTag::Short(v) => Some(*v as i32),
+        NbtTag::Int(v) => Some(*v),
+        NbtTag::Long(v) => Some(*v as i32),
+        NbtTag::Float(v) => Some(v.floor() as i32),
+        NbtTag::Double(v) => Some(v.floor() as i32),
+        _ => None,
+    }
+}
+
+fn numeric_as_i64(tag: &NbtTag) -> Option<i64> {
+    match tag {
+        NbtTag::Long(v) => Some(*v),
+        NbtTag::Float(v) => Some(v.floor() as i64),
+        NbtTag::Double(v) => Some(v.floor() as i64),
+        other => numeric_as_i32(other).map(i64::from),
+    }
+}
+
+fn set_element(tag: &mut NbtTag, i: usize, value: NbtTag) -> bool {
+    match tag {
+        NbtTag::List { .. } => tag.set_at(i, value).is_ok(),
+        NbtTag::ByteArray(values) => numeric_as_i32(&value)
+            .map(|v| values[i] = v as i8)
+            .is_some(),
+        NbtTag::IntArray(values) => numeric_as_i32(&value).map(|v| values[i] = v).is_some(),
+        NbtTag::LongArray(values) => numeric_as_i64(&value).map(|v| values[i] = v).is_some(),
+        _ => false,
+    }
+}
+
+fn add_element(tag: &mut NbtTag, i: usize, value: NbtTag) -> bool {
+    match tag {
+        NbtTag::List { .. } => tag.insert_at(i, value).is_ok(),
+        NbtTag::ByteArray(values) => numeric_as_i32(&value)
+            .map(|v| values.insert(i, v as i8))
+            .is_some(),
+        NbtTag::IntArray(values) => numeric_as_i32(&value)
+            .map(|v| values.insert(i, v))
+            .is_some(),
+        NbtTag::LongArray(values) => numeric_as_i64(&value)
+            .map(|v| values.insert(i, v))
+            .is_some(),
+        _ => false,
+    }
+}
+
+fn remove_element(tag: &mut NbtTag, i: usize) -> bool {
+    match tag {
+        NbtTag::List { .. } => {
+            let _ = tag.remove_at(i);
+        }
+        NbtTag::ByteArray(values) => {
+            values.remove(i);
+        }
+        NbtTag::IntArray(values) => {
+            values.remove(i);
+        }
+        NbtTag::LongArray(values) => {
+            values.remove(i);
+        }
+        _ => return false,
+    }
+    true
+}
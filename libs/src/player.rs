@@ -0,0 +1,404 @@
+use crate::{format_uuid, CompoundMap, NbtError, NbtFile, NbtTag, Result};
+use std::path::Path;
+
+// 1.21.5 moved armor and offhand from `Inventory` to the `equipment` compound
+const EQUIPMENT_DATA_VERSION: i32 = 4325;
+
+/// Slot of the player inventory or ender chest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InventorySlot {
+    /// Hotbar slot 0-8
+    Hotbar(u8),
+    /// Main inventory slot 0-26 (stored as slots 9-35)
+    Main(u8),
+    Feet,
+    Legs,
+    Chest,
+    Head,
+    Offhand,
+    /// Ender chest slot 0-26
+    EnderChest(u8),
+}
+
+impl InventorySlot {
+    /// Slot for a `Slot` byte of the `Inventory` list
+    pub fn from_inventory(slot: i8) -> Option<Self> {
+        match slot {
+            0..=8 => Some(Self::Hotbar(slot as u8)),
+            9..=35 => Some(Self::Main(slot as u8 - 9)),
+            100 => Some(Self::Feet),
+            101 => Some(Self::Legs),
+            102 => Some(Self::Chest),
+            103 => Some(Self::Head),
+            -106 => Some(Self::Offhand),
+            _ => None,
+        }
+    }
+
+    /// List key and `Slot` byte the item is stored with
+    pub fn raw(self) -> Result<(&'static str, i8)> {
+        let (key, slot, len) = match self {
+            Self::Hotbar(i) => ("Inventory", i, 9),
+            Self::Main(i) => ("Inventory", i, 27),
+            Self::EnderChest(i) => ("EnderItems", i, 27),
+            Self::Feet => return Ok(("Inventory", 100)),
+            Self::Legs => return Ok(("Inventory", 101)),
+            Self::Chest => return Ok(("Inventory", 102)),
+            Self::Head => return Ok(("Inventory", 103)),
+            Self::Offhand => return Ok(("Inventory", -106)),
+        };
+        if slot >= len {
+            return Err(NbtError::IndexOutOfBounds {
+                index: slot as i64,
+                len: len as usize,
+            });
+        }
+        let offset = if matches!(self, Self::Main(_)) { 9 } else { 0 };
+        Ok((key, (slot + offset) as i8))
+    }
+
+    // Key in the `equipment` compound of newer files
+    fn equipment_key(self) -> Option<&'static str> {
+        match self {
+            Self::Feet => Some("feet"),
+            Self::Legs => Some("legs"),
+            Self::Chest => Some("chest"),
+            Self::Head => Some("head"),
+            Self::Offhand => Some("offhand"),
+            _ => None,
+        }
+    }
+}
+
+/// Typed editor for player files (`playerdata/<uuid>.dat`)
+///
+/// Like `LevelData`, accessors work on the root compound in place so unknown keys survive,
+/// and the file is written back with its original compression and root name.
+#[derive(Debug, Clone)]
+pub struct PlayerData {
+    file: NbtFile,
+}
+
+impl PlayerData {
+    pub fn read(data: &[u8]) -> Result<Self> {
+        Self::from_file(NbtFile::read(data, None)?)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file(NbtFile::from_path(path)?)
+    }
+
+    /// Wrap a parsed file, which must have a compound root
+    pub fn from_file(file: NbtFile) -> Result<Self> {
+        if !file.root.is_compound() {
+            return Err(NbtError::type_mismatch("Compound", file.root.type_name()));
+        }
+        Ok(Self { file })
+    }
+
+    pub fn write(&self) -> Result<Vec<u8>> {
+        self.file.write()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.file.save(path)
+    }
+
+    pub fn file(&self) -> &NbtFile {
+        &self.file
+    }
+
+    pub fn into_file(self) -> NbtFile {
+        self.file
+    }
+
+    pub fn root(&self) -> &NbtTag {
+        &self.file.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut CompoundMap {
+        self.file
+            .root
+            .as_compound_mut()
+            .expect("checked by from_file")
+    }
+
+    /// Player UUID from `UUID` (or the `UUIDMost` / `UUIDLeast` pair of older files)
+    pub fn uuid(&self) -> Result<u128> {
+        self.root().try_get_uuid("UUID")
+    }
+
+    /// Player UUID in the hyphenated form used for file names
+    pub fn uuid_string(&self) -> Result<String> {
+        self.uuid().map(format_uuid)
+    }
+
+    pub fn set_uuid(&mut self, uuid: u128) {
+        self.file.root.set_uuid("UUID", uuid);
+    }
+
+    /// Position from `Pos`
+    pub fn position(&self) -> Result<(f64, f64, f64)> {
+        let pos = self.root().try_get_list_of::<f64>("Pos")?;
+        match pos[..] {
+            [x, y, z] => Ok((x, y, z)),
+            _ => Err(NbtError::type_mismatch("List of 3 doubles", "List").at_key("Pos")),
+        }
+    }
+
+    pub fn set_position(&mut self, x: f64, y: f64, z: f64) {
+        self.set("Pos", doubles(&[x, y, z]));
+    }
+
+    /// Dimension id (`minecraft:overworld`, ...)
+    pub fn dimension(&self) -> Result<&str> {
+        self.root().try_get_str("Dimension")
+    }
+
+    /// Move the player, stopping its motion and fall; changing dimension also dismounts it
+    pub fn teleport(&mut self, x: f64, y: f64, z: f64, dimension: &str) {
+        if self.dimension().ok() != Some(dimension) {
+            self.file.root.remove("RootVehicle");
+        }
+        self.set_position(x, y, z);
+        self.set("Dimension", NbtTag::String(dimension.to_string()));
+        self.set("Motion", doubles(&[0.0, 0.0, 0.0]));
+        for (key, zero) in [
+            ("FallDistance", NbtTag::Float(0.0)),
+            ("fall_distance", NbtTag::Double(0.0)),
+        ] {
+            if self.root().get(key).is_some() {
+                self.set(key, zero);
+            }
+        }
+    }
+
+    /// Yaw and pitch from `Rotation`
+    pub fn rotation(&self) -> Result<(f32, f32)> {
+        let rotation = self.root().try_get_list_of::<f32>("Rotation")?;
+        match rotation[..] {
+            [yaw, pitch] => Ok((yaw, pitch)),
+            _ => Err(NbtError::type_mismatch("List of 2 floats", "List").at_key("Rotation")),
+        }
+    }
+
+    pub fn health(&self) -> Result<f32> {
+        self.root().try_get_f32("Health")
+    }
+
+    pub fn set_health(&mut self, health: f32) {
+        self.set("Health", NbtTag::Float(health));
+    }
+
+    pub fn game_mode(&self) -> Result<i32> {
+        self.root().try_get_i32("playerGameType")
+    }
+
+    pub fn set_game_mode(&mut self, game_mode: i32) {
+        self.set("playerGameType", NbtTag::Int(game_mode));
+    }
+
+    pub fn xp_level(&self) -> Result<i32> {
+        self.root().try_get_i32("XpLevel")
+    }
+
+    pub fn set_xp_level(&mut self, level: i32) {
+        self.set("XpLevel", NbtTag::Int(level));
+    }
+
+    /// `abilities` compound (`mayfly`, `flying`, `instabuild`, `flySpeed`, ...)
+    pub fn abilities(&self) -> Result<&CompoundMap> {
+        self.root().try_get_compound("abilities")
+    }
+
+    pub fn abilities_mut(&mut self) -> Result<&mut CompoundMap> {
+        self.file.root.try_get_compound_mut("abilities")
+    }
+
+    /// Base value of an attribute, by id with or without the `minecraft:` namespace
+    ///
+    /// Reads both the `attributes` (`id`/`base`) and the older `Attributes` (`Name`/`Base`) layout.
+    pub fn attribute_base(&self, name: &str) -> Option<f64> {
+        let (list, index, base_key) = self.find_attribute(name)?;
+        let (_, items) = self.root().get(list)?.as_list()?;
+        items[index].get(base_key).map(NbtTag::as_number)
+    }
+
+    /// Set the base value of an existing attribute (returns whether it was found)
+    pub fn set_attribute_base(&mut self, name: &str, base: f64) -> bool {
+        let Some((list, index, base_key)) = self.find_attribute(name) else {
+            return false;
+        };
+        let Some(NbtTag::List { items, .. }) = self.root_mut().get_mut(list) else {
+            return false;
+        };
+        match items[index].as_compound_mut() {
+            Some(attribute) => {
+                attribute.insert(base_key.to_string(), NbtTag::Double(base));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Item in a slot (the item compound, including its `Slot` byte in inventory lists)
+    pub fn item(&self, slot: InventorySlot) -> Result<Option<&CompoundMap>> {
+        if let Some(key) = self.equipment_key(slot) {
+            return Ok(self
+                .root()
+                .get_compound("equipment")
+                .and_then(|equipment| equipment.get(key))
+                .and_then(NbtTag::as_compound));
+        }
+        let (list, raw) = slot.raw()?;
+        Ok(self.root().get(list).and_then(|items| {
+            let (_, items) = items.as_list()?;
+            items
+                .iter()
+                .filter_map(NbtTag::as_compound)
+                .find(|item| slot_of(item) == Some(raw))
+        }))
+    }
+
+    /// Put an item in a slot, replacing and returning the previous one
+    pub fn set_item(
+        &mut self,
+        slot: InventorySlot,
+        mut item: CompoundMap,
+    ) -> Result<Option<CompoundMap>> {
+        let previous = self.remove_item(slot)?;
+        if let Some(key) = self.equipment_key(slot) {
+            let mut item = NbtTag::Compound(item);
+            item.remove("Slot");
+            let equipment = self
+                .root_mut()
+                .entry("equipment".to_string())
+                .or_insert_with(NbtTag::compound);
+            let found = equipment.type_name();
+            let Some(equipment) = equipment.as_compound_mut() else {
+                return Err(NbtError::type_mismatch("Compound", found).at_key("equipment"));
+            };
+            equipment.insert(key.to_string(), item);
+            return Ok(previous);
+        }
+        let (list, raw) = slot.raw()?;
+        item.insert("Slot".to_string(), NbtTag::Byte(raw));
+        self.root_mut()
+            .entry(list.to_string())
+            .or_insert_with(|| NbtTag::list(10))
+            .push(NbtTag::Compound(item))
+            .map_err(|e| e.at_key(list))?;
+        Ok(previous)
+    }
+
+    /// Remove and return the item in a slot
+    pub fn remove_item(&mut self, slot: InventorySlot) -> Result<Option<CompoundMap>> {
+        if let Some(key) = self.equipment_key(slot) {
+            let removed = match self.root_mut().get_mut("equipment") {
+                Some(equipment) => equipment.remove(key),
+                None => None,
+            };
+            return Ok(removed.and_then(into_compound));
+        }
+        let (list, raw) = slot.raw()?;
+        let Some(items) = self.root_mut().get_mut(list) else {
+            return Ok(None);
+        };
+        let position = items.as_list().and_then(|(_, items)| {
+            items
+                .iter()
+                .position(|item| item.as_compound().and_then(slot_of) == Some(raw))
+        });
+        match position {
+            Some(index) => Ok(into_compound(items.remove_at(index)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// All items with a known slot: inventory, then equipment, then ender chest
+    pub fn items(&self) -> Vec<(InventorySlot, &CompoundMap)> {
+        let list = |key| {
+            self.root()
+                .get(key)
+                .and_then(NbtTag::as_list)
+                .into_iter()
+                .flat_map(|(_, items)| items.iter().filter_map(NbtTag::as_compound))
+        };
+        let mut found: Vec<_> = list("Inventory")
+            .filter_map(|item| Some((InventorySlot::from_inventory(slot_of(item)?)?, item)))
+            .collect();
+        for slot in [
+            InventorySlot::Feet,
+            InventorySlot::Legs,
+            InventorySlot::Chest,
+            InventorySlot::Head,
+            InventorySlot::Offhand,
+        ] {
+            if self.equipment_key(slot).is_some() {
+                if let Ok(Some(item)) = self.item(slot) {
+                    found.push((slot, item));
+                }
+            }
+        }
+        found.extend(list("EnderItems").filter_map(|item| match slot_of(item)? {
+            slot @ 0..=26 => Some((InventorySlot::EnderChest(slot as u8), item)),
+            _ => None,
+        }));
+        found
+    }
+
+    fn set(&mut self, key: &str, value: NbtTag) {
+        self.root_mut().insert(key.to_string(), value);
+    }
+
+    // Equipment key when the slot is stored in the `equipment` compound of this file
+    fn equipment_key(&self, slot: InventorySlot) -> Option<&'static str> {
+        let uses_equipment = self.root().get("equipment").is_some()
+            || self
+                .root()
+                .try_get_i32("DataVersion")
+                .is_ok_and(|version| version >= EQUIPMENT_DATA_VERSION);
+        slot.equipment_key().filter(|_| uses_equipment)
+    }
+
+    // List key, element index and base value key of an attribute
+    fn find_attribute(&self, name: &str) -> Option<(&'static str, usize, &'static str)> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        for (list, id_key, base_key) in
+            [("attributes", "id", "base"), ("Attributes", "Name", "Base")]
+        {
+            let Some((_, items)) = self.root().get(list).and_then(NbtTag::as_list) else {
+                continue;
+            };
+            let index = items.iter().position(|attribute| {
+                let id = attribute.get_string(id_key);
+                id.strip_prefix("minecraft:").unwrap_or(id) == name
+            });
+            if let Some(index) = index {
+                return Some((list, index, base_key));
+            }
+        }
+        None
+    }
+}
+
+fn slot_of(item: &CompoundMap) -> Option<i8> {
+    match item.get("Slot") {
+        Some(NbtTag::Byte(slot)) => Some(*slot),
+        _ => None,
+    }
+}
+
+fn into_compound(tag: NbtTag) -> Option<CompoundMap> {
+    match tag {
+        NbtTag::Compound(map) => Some(map),
+        _ => None,
+    }
+}
+
+fn doubles(values: &[f64]) -> NbtTag {
+    NbtTag::List {
+        tag_type: 6,
+        items: values.iter().map(|v| NbtTag::Double(*v)).collect(),
+    }
+}
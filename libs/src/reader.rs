@@ -1,5 +1,6 @@
-use crate::{NbtError, NbtTag, Result};
-use std::collections::{HashMap, HashSet};
+use crate::path::{get_all_nodes, get_below, resolve_index};
+use crate::{CompoundMap, NbtError, NbtPath, NbtTag, PathNode, Result};
+use std::collections::HashSet;
 
 /// Endianness for NBT data
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,22 +9,89 @@ pub enum Endian {
     Little, // Bedrock Edition
 }
 
+/// Limits applied while reading NBT
+///
+/// The default only checks the vanilla nesting depth, like files loaded from disk by the game.
+/// Use `network` or `untrusted` (or custom values) when the input comes from untrusted sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtLimits {
+    /// Maximum nesting of compounds and lists (vanilla uses 512)
+    pub max_depth: usize,
+    /// Maximum estimated heap allocation for the decoded tree, in bytes (vanilla's `NbtAccounter`)
+    pub max_allocation: usize,
+    /// Maximum size of decompressed data, in bytes
+    pub max_decompressed_size: usize,
+}
+
+impl NbtLimits {
+    /// Quota used by vanilla for NBT received over the network (2 MiB)
+    pub fn network() -> Self {
+        Self {
+            max_allocation: 2 * 1024 * 1024,
+            max_decompressed_size: 2 * 1024 * 1024,
+            ..Self::unlimited()
+        }
+    }
+
+    /// Generous caps for untrusted files (256 MiB allocation and decompressed size)
+    pub fn untrusted() -> Self {
+        Self {
+            max_allocation: 256 * 1024 * 1024,
+            max_decompressed_size: 256 * 1024 * 1024,
+            ..Self::unlimited()
+        }
+    }
+
+    /// No limits besides the vanilla nesting depth
+    pub fn unlimited() -> Self {
+        Self {
+            max_depth: 512,
+            max_allocation: usize::MAX,
+            max_decompressed_size: usize::MAX,
+        }
+    }
+}
+
+impl Default for NbtLimits {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
 /// Zero-copy NBT reader with streaming capabilities
 pub struct NbtReader<'a> {
     data: &'a [u8],
     pub cursor: usize,
     endian: Endian,
+    limits: NbtLimits,
+    depth: usize,
+    allocated: usize,
 }
 
 impl<'a> NbtReader<'a> {
     pub fn new(data: &'a [u8], endian: Endian) -> Self {
+        Self::with_limits(data, endian, NbtLimits::unlimited())
+    }
+
+    pub fn with_limits(data: &'a [u8], endian: Endian, limits: NbtLimits) -> Self {
         Self {
             data,
             cursor: 0,
             endian,
+            limits,
+            depth: 0,
+            allocated: 0,
         }
     }
 
+    pub fn limits(&self) -> &NbtLimits {
+        &self.limits
+    }
+
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
     pub fn remaining(&self) -> usize {
         self.data.len().saturating_sub(self.cursor)
     }
@@ -81,13 +149,85 @@ impl<'a> NbtReader<'a> {
     }
 
     pub fn read_string(&mut self) -> Result<String> {
-        let len = self.read_i16()? as usize;
+        let len = self.read_i16()? as u16 as usize;
+        self.account(len)?;
         let bytes = self.read_bytes(len)?;
         String::from_utf8(bytes.to_vec())
             .map_err(|e| NbtError::Parse(format!("Invalid UTF-8: {}", e)))
     }
 
+    /// Read a string borrowed from the input
+    pub fn read_str(&mut self) -> Result<&'a str> {
+        let len = self.read_i16()? as u16 as usize;
+        let bytes = self.read_bytes(len)?;
+        std::str::from_utf8(bytes).map_err(|e| NbtError::Parse(format!("Invalid UTF-8: {}", e)))
+    }
+
+    /// Read an array or list length, rejecting negative values
+    pub fn read_len(&mut self) -> Result<usize> {
+        let len = self.read_i32()?;
+        usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+    }
+
+    /// Read an array length and borrow its payload (`element_size` bytes per element)
+    pub fn read_array_bytes(&mut self, element_size: usize) -> Result<&'a [u8]> {
+        let len = self.read_len()?;
+        self.read_bytes(
+            len.checked_mul(element_size)
+                .ok_or(NbtError::UnexpectedEof)?,
+        )
+    }
+
+    // Enter a compound or list, checking the nesting limit
+    pub(crate) fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.limits.max_depth {
+            return Err(NbtError::DepthLimitExceeded {
+                limit: self.limits.max_depth,
+            });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    // Charge an allocation against the limit before making it
+    fn account(&mut self, bytes: usize) -> Result<()> {
+        self.allocated = self.allocated.saturating_add(bytes);
+        if self.allocated > self.limits.max_allocation {
+            return Err(NbtError::AllocationLimitExceeded {
+                limit: self.limits.max_allocation,
+            });
+        }
+        Ok(())
+    }
+
+    // Fail early when the input cannot hold `len` elements of `size` bytes
+    fn check_remaining(&self, len: usize, size: usize) -> Result<()> {
+        match len.checked_mul(size) {
+            Some(total) if total <= self.remaining() => Ok(()),
+            _ => Err(NbtError::UnexpectedEof),
+        }
+    }
+
     pub fn read_tag(&mut self, tag_type: u8) -> Result<NbtTag> {
+        // Containers recurse: keep this frame small so deep (but allowed) nesting fits the stack
+        if !matches!(tag_type, 9 | 10) {
+            return self.read_scalar(tag_type);
+        }
+        self.enter()?;
+        let tag = if tag_type == 9 {
+            self.read_list()
+        } else {
+            self.read_compound()
+        };
+        self.leave();
+        tag
+    }
+
+    fn read_scalar(&mut self, tag_type: u8) -> Result<NbtTag> {
         match tag_type {
             0 => Ok(NbtTag::End),
             1 => Ok(NbtTag::Byte(self.read_i8()?)),
@@ -98,8 +238,6 @@ impl<'a> NbtReader<'a> {
             6 => Ok(NbtTag::Double(self.read_f64()?)),
             7 => self.read_byte_array(),
             8 => Ok(NbtTag::String(self.read_string()?)),
-            9 => self.read_list(),
-            10 => self.read_compound(),
             11 => self.read_int_array(),
             12 => self.read_long_array(),
             _ => Err(NbtError::InvalidTagType(tag_type)),
@@ -107,17 +245,26 @@ impl<'a> NbtReader<'a> {
     }
 
     fn read_byte_array(&mut self) -> Result<NbtTag> {
-        let len = self.read_i32()? as usize;
-        let mut array = Vec::with_capacity(len);
-        for _ in 0..len {
-            array.push(self.read_i8()?);
+        let bytes = self.read_array_bytes(1)?;
+        self.account(bytes.len())?;
+        Ok(NbtTag::ByteArray(bytes.iter().map(|b| *b as i8).collect()))
+    }
+
+    // Element type and length of a list, rejecting lengths the input cannot hold
+    fn read_list_header(&mut self) -> Result<(u8, usize)> {
+        let tag_type = self.read_u8()?;
+        let len = self.read_len()?;
+        if tag_type == 0 && len > 0 {
+            return Err(NbtError::Parse("Non-empty list of End tags".to_string()));
         }
-        Ok(NbtTag::ByteArray(array))
+        // Every element takes at least one byte of input
+        self.check_remaining(len, 1)?;
+        Ok((tag_type, len))
     }
 
     fn read_list(&mut self) -> Result<NbtTag> {
-        let tag_type = self.read_u8()?;
-        let len = self.read_i32()? as usize;
+        let (tag_type, len) = self.read_list_header()?;
+        self.account(len.saturating_mul(std::mem::size_of::<NbtTag>()))?;
         let mut items = Vec::with_capacity(len);
 
         for _ in 0..len {
@@ -128,7 +275,7 @@ impl<'a> NbtReader<'a> {
     }
 
     fn read_compound(&mut self) -> Result<NbtTag> {
-        let mut map = HashMap::new();
+        let mut map = CompoundMap::new();
 
         loop {
             let tag_type = self.read_u8()?;
@@ -137,6 +284,7 @@ impl<'a> NbtReader<'a> {
             }
 
             let name = self.read_string()?;
+            self.account(std::mem::size_of::<(String, NbtTag)>())?;
             let value = self.read_tag(tag_type)?;
             map.insert(name, value);
         }
@@ -146,7 +294,7 @@ impl<'a> NbtReader<'a> {
 
     /// Parse seulement les champs spécifiés pour optimiser la performance
     pub fn read_compound_selective(&mut self, wanted_fields: &[&str]) -> Result<NbtTag> {
-        let mut map = HashMap::new();
+        let mut map = CompoundMap::new();
         let wanted_set: HashSet<&str> = wanted_fields.iter().copied().collect();
 
         loop {
@@ -170,114 +318,236 @@ impl<'a> NbtReader<'a> {
     }
 
     fn read_int_array(&mut self) -> Result<NbtTag> {
-        let len = self.read_i32()? as usize;
-        let mut array = Vec::with_capacity(len);
-        for _ in 0..len {
-            array.push(self.read_i32()?);
-        }
-        Ok(NbtTag::IntArray(array))
+        let bytes = self.read_array_bytes(4)?;
+        self.account(bytes.len())?;
+        Ok(NbtTag::IntArray(decode_i32s(bytes, self.endian)))
     }
 
     fn read_long_array(&mut self) -> Result<NbtTag> {
-        let len = self.read_i32()? as usize;
-        let mut array = Vec::with_capacity(len);
-        for _ in 0..len {
-            array.push(self.read_i64()?);
-        }
-        Ok(NbtTag::LongArray(array))
+        let bytes = self.read_array_bytes(8)?;
+        self.account(bytes.len())?;
+        Ok(NbtTag::LongArray(decode_i64s(bytes, self.endian)))
     }
 
     // Streaming methods
     pub fn skip_tag(&mut self, tag_type: u8) -> Result<()> {
-        match tag_type {
-            0 => {}
-            1 => {
-                self.cursor += 1;
-            }
-            2 => {
-                self.cursor += 2;
-            }
-            3 => {
-                self.cursor += 4;
-            }
-            4 => {
-                self.cursor += 8;
-            }
-            5 => {
-                self.cursor += 4;
-            }
-            6 => {
-                self.cursor += 8;
-            }
-            7 => {
-                let len = self.read_i32()? as usize;
-                self.cursor += len;
-            }
-            8 => {
-                let len = self.read_i16()? as usize;
-                self.cursor += len;
-            }
-            9 => {
-                let list_type = self.read_u8()?;
-                let len = self.read_i32()? as usize;
-                for _ in 0..len {
-                    self.skip_tag(list_type)?;
-                }
-            }
-            10 => loop {
-                let tag_type = self.read_u8()?;
-                if tag_type == 0 {
-                    break;
-                }
-                self.read_string()?;
-                self.skip_tag(tag_type)?;
-            },
-            11 => {
-                let len = self.read_i32()? as usize;
-                self.cursor += len * 4;
-            }
-            12 => {
-                let len = self.read_i32()? as usize;
-                self.cursor += len * 8;
+        if !matches!(tag_type, 9 | 10) {
+            return self.skip_scalar(tag_type);
+        }
+        self.enter()?;
+        let skipped = if tag_type == 9 {
+            self.skip_list()
+        } else {
+            self.skip_compound()
+        };
+        self.leave();
+        skipped
+    }
+
+    fn skip_scalar(&mut self, tag_type: u8) -> Result<()> {
+        let len = match tag_type {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 | 5 => 4,
+            4 | 6 => 8,
+            7 | 11 | 12 => {
+                let size = match tag_type {
+                    7 => 1,
+                    11 => 4,
+                    _ => 8,
+                };
+                self.read_array_bytes(size)?;
+                return Ok(());
             }
+            8 => self.read_i16()? as u16 as usize,
             _ => return Err(NbtError::InvalidTagType(tag_type)),
+        };
+        self.read_bytes(len)?;
+        Ok(())
+    }
+
+    fn skip_list(&mut self) -> Result<()> {
+        let (list_type, len) = self.read_list_header()?;
+        for _ in 0..len {
+            self.skip_tag(list_type)?;
         }
         Ok(())
     }
 
+    fn skip_compound(&mut self) -> Result<()> {
+        loop {
+            let tag_type = self.read_u8()?;
+            if tag_type == 0 {
+                return Ok(());
+            }
+            self.skip_scalar(8)?;
+            self.skip_tag(tag_type)?;
+        }
+    }
+
+    /// Find the first value at an NBT path (`Level.Status`, `sections[0].Y`) without building the tree
     pub fn find_path(&mut self, path: &str) -> Result<Option<NbtTag>> {
-        let original_cursor = self.cursor;
+        let path = NbtPath::parse(path)?;
+        Ok(self.extract_path(&path)?.into_iter().next())
+    }
+
+    /// Extract every match of `path` from the root compound
+    pub fn extract_path(&mut self, path: &NbtPath) -> Result<Vec<NbtTag>> {
+        Ok(self
+            .extract_paths(std::slice::from_ref(path))?
+            .pop()
+            .unwrap_or_default())
+    }
+
+    /// Extract several paths in a single pass over the data.
+    ///
+    /// Subtrees that no path goes through are skipped without allocating; only matched
+    /// values (and children tested against a `{...}` pattern) are read into `NbtTag`s.
+    pub fn extract_paths(&mut self, paths: &[NbtPath]) -> Result<Vec<Vec<NbtTag>>> {
+        let (original_cursor, original_depth) = (self.cursor, self.depth);
         self.cursor = 0;
+        self.depth = 0;
+
+        let result = self.extract_from_root(paths);
+
+        self.cursor = original_cursor;
+        self.depth = original_depth;
+        result
+    }
 
+    fn extract_from_root(&mut self, paths: &[NbtPath]) -> Result<Vec<Vec<NbtTag>>> {
         let tag_type = self.read_u8()?;
         if tag_type != 10 {
             return Err(NbtError::InvalidTagType(tag_type));
         }
-
         let _root_name = self.read_string()?;
-        let result = self.find_in_compound(path);
 
-        self.cursor = original_cursor;
-        result
-    }
+        let active: Vec<(usize, &[PathNode])> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (i, path.nodes()))
+            .collect();
+        let mut out = vec![Vec::new(); paths.len()];
+        self.extract_tag(tag_type, &active, &mut out)?;
+        Ok(out)
+    }
+
+    fn extract_tag(
+        &mut self,
+        tag_type: u8,
+        active: &[(usize, &[PathNode])],
+        out: &mut [Vec<NbtTag>],
+    ) -> Result<()> {
+        let streamable = matches!(tag_type, 9 | 10)
+            && active
+                .iter()
+                .all(|(_, nodes)| !matches!(nodes.first(), None | Some(PathNode::MatchRoot(_))));
+        if !streamable {
+            let tag = self.read_tag(tag_type)?;
+            for (i, nodes) in active {
+                out[*i].extend(
+                    get_all_nodes(nodes, &tag)
+                        .into_iter()
+                        .map(|t| t.into_owned()),
+                );
+            }
+            return Ok(());
+        }
 
-    fn find_in_compound(&mut self, target_path: &str) -> Result<Option<NbtTag>> {
-        loop {
-            let tag_type = self.read_u8()?;
-            if tag_type == 0 {
-                break;
+        if tag_type == 10 {
+            loop {
+                let entry_type = self.read_u8()?;
+                if entry_type == 0 {
+                    break;
+                }
+                let len = self.read_i16()? as u16 as usize;
+                let key = self.read_bytes(len)?;
+
+                let selected: Vec<_> = active
+                    .iter()
+                    .filter(|(_, nodes)| match &nodes[0] {
+                        PathNode::Child(name) | PathNode::MatchObject(name, _) => {
+                            name.as_bytes() == key
+                        }
+                        _ => false,
+                    })
+                    .map(|(i, nodes)| (*i, &nodes[0], &nodes[1..]))
+                    .collect();
+                self.extract_child(entry_type, &selected, out)?;
+            }
+        } else {
+            let (element_type, len) = self.read_list_header()?;
+            for index in 0..len {
+                let selected: Vec<_> = active
+                    .iter()
+                    .filter(|(_, nodes)| match &nodes[0] {
+                        PathNode::Index(i) => resolve_index(*i, len) == Some(index),
+                        PathNode::All | PathNode::MatchElement(_) => true,
+                        _ => false,
+                    })
+                    .map(|(i, nodes)| (*i, &nodes[0], &nodes[1..]))
+                    .collect();
+                self.extract_child(element_type, &selected, out)?;
             }
+        }
+        Ok(())
+    }
 
-            let name = self.read_string()?;
+    // `selected` holds (path, node that selected the child, remaining nodes)
+    fn extract_child(
+        &mut self,
+        tag_type: u8,
+        selected: &[(usize, &PathNode, &[PathNode])],
+        out: &mut [Vec<NbtTag>],
+    ) -> Result<()> {
+        if selected.is_empty() {
+            return self.skip_tag(tag_type);
+        }
 
-            if name == target_path {
-                return Ok(Some(self.read_tag(tag_type)?));
+        let materialize = selected.iter().any(|(_, node, rest)| {
+            rest.is_empty() || matches!(node, PathNode::MatchObject(..) | PathNode::MatchElement(_))
+        });
+        if materialize {
+            let child = self.read_tag(tag_type)?;
+            for (i, node, rest) in selected {
+                out[*i].extend(
+                    get_below(node, rest, &child)
+                        .into_iter()
+                        .map(|t| t.into_owned()),
+                );
             }
-
-            self.skip_tag(tag_type)?;
+            return Ok(());
         }
 
-        Ok(None)
+        let active: Vec<(usize, &[PathNode])> =
+            selected.iter().map(|(i, _, rest)| (*i, *rest)).collect();
+        self.extract_tag(tag_type, &active, out)
+    }
+}
+
+// Bulk big/little-endian conversion; the endian check is hoisted so the loop vectorizes
+pub(crate) fn decode_i32s(bytes: &[u8], endian: Endian) -> Vec<i32> {
+    let chunks = bytes.chunks_exact(4);
+    match endian {
+        Endian::Big => chunks
+            .map(|c| i32::from_be_bytes(c.try_into().unwrap()))
+            .collect(),
+        Endian::Little => chunks
+            .map(|c| i32::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
+    }
+}
+
+pub(crate) fn decode_i64s(bytes: &[u8], endian: Endian) -> Vec<i64> {
+    let chunks = bytes.chunks_exact(8);
+    match endian {
+        Endian::Big => chunks
+            .map(|c| i64::from_be_bytes(c.try_into().unwrap()))
+            .collect(),
+        Endian::Little => chunks
+            .map(|c| i64::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
     }
 }
 
@@ -344,7 +614,17 @@ impl NbtWriter {
         self.buffer.extend_from_slice(value.as_bytes());
     }
 
+    /// Write a tag payload; on error (invalid list) nothing is left in the buffer
     pub fn write_tag(&mut self, tag: &NbtTag) -> Result<()> {
+        let start = self.buffer.len();
+        let result = self.write_payload(tag);
+        if result.is_err() {
+            self.buffer.truncate(start);
+        }
+        result
+    }
+
+    fn write_payload(&mut self, tag: &NbtTag) -> Result<()> {
         match tag {
             NbtTag::End => {}
             NbtTag::Byte(v) => self.write_i8(*v),
@@ -355,8 +635,8 @@ impl NbtWriter {
             NbtTag::Double(v) => self.write_f64(*v),
             NbtTag::ByteArray(array) => self.write_byte_array(array),
             NbtTag::String(s) => self.write_string(s),
-            NbtTag::List { tag_type, items } => self.write_list(*tag_type, items),
-            NbtTag::Compound(map) => self.write_compound(map),
+            NbtTag::List { tag_type, items } => self.write_list(*tag_type, items)?,
+            NbtTag::Compound(map) => self.write_compound(map)?,
             NbtTag::IntArray(array) => self.write_int_array(array),
             NbtTag::LongArray(array) => self.write_long_array(array),
         }
@@ -370,21 +650,36 @@ impl NbtWriter {
         }
     }
 
-    fn write_list(&mut self, tag_type: u8, items: &[NbtTag]) {
+    fn write_list(&mut self, tag_type: u8, items: &[NbtTag]) -> Result<()> {
+        if tag_type == 0 && !items.is_empty() {
+            return Err(NbtError::InvalidTagType(0));
+        }
         self.write_u8(tag_type);
         self.write_i32(items.len() as i32);
         for item in items {
-            let _ = self.write_tag(item);
+            if item.type_id() != tag_type {
+                return Err(NbtError::ListTypeMismatch {
+                    expected: tag_type,
+                    found: item.type_id(),
+                });
+            }
+            self.write_payload(item)?;
         }
+        Ok(())
     }
 
-    fn write_compound(&mut self, map: &HashMap<String, NbtTag>) {
+    fn write_compound(&mut self, map: &CompoundMap) -> Result<()> {
         for (name, tag) in map {
+            // A type 0 entry would read back as the end of the compound
+            if matches!(tag, NbtTag::End) {
+                return Err(NbtError::InvalidTagType(0));
+            }
             self.write_u8(tag.type_id());
             self.write_string(name);
-            let _ = self.write_tag(tag);
+            self.write_payload(tag)?;
         }
         self.write_u8(0); // End tag
+        Ok(())
     }
 
     fn write_int_array(&mut self, array: &[i32]) {
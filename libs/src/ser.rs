@@ -0,0 +1,473 @@
+use crate::{tag_type_name, CompoundMap, Endian, NbtError, NbtTag, NbtWriter, Result};
+use serde::ser::{self, Serialize};
+
+// Newtype struct names recognised by `TagSerializer` to pick array tags
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "__nbt_byte_array";
+pub(crate) const INT_ARRAY_TOKEN: &str = "__nbt_int_array";
+pub(crate) const LONG_ARRAY_TOKEN: &str = "__nbt_long_array";
+pub(crate) const TAG_TOKEN: &str = "__nbt_tag";
+
+impl ser::Error for NbtError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NbtError::Serde(msg.to_string())
+    }
+}
+
+/// Serialize a value into an NBT tag
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<NbtTag> {
+    value.serialize(TagSerializer)
+}
+
+/// Serialize a value into uncompressed big-endian NBT bytes with a root name
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T, root_name: &str) -> Result<Vec<u8>> {
+    let tag = to_tag(value)?;
+    if !tag.is_compound() {
+        return Err(NbtError::Serde(format!(
+            "Root must serialize to a Compound, found {}",
+            tag.type_name()
+        )));
+    }
+
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer.write_u8(10);
+    writer.write_string(root_name);
+    writer.write_tag(&tag)?;
+    Ok(writer.into_bytes())
+}
+
+/// Serde serializer producing `NbtTag` values
+///
+/// `Option::None` and unit values produce `NbtTag::End`, which is
+/// omitted from compounds.
+pub struct TagSerializer;
+
+impl ser::Serializer for TagSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = VariantSerializer<CompoundSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<NbtTag> {
+        Ok(NbtTag::Byte(v as i8))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<NbtTag> {
+        Ok(NbtTag::Byte(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<NbtTag> {
+        Ok(NbtTag::Short(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<NbtTag> {
+        Ok(NbtTag::Int(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<NbtTag> {
+        Ok(NbtTag::Long(v))
+    }
+
+    // Unsigned integers keep their bit pattern in the signed tag of the same width
+    fn serialize_u8(self, v: u8) -> Result<NbtTag> {
+        Ok(NbtTag::Byte(v as i8))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<NbtTag> {
+        Ok(NbtTag::Short(v as i16))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<NbtTag> {
+        Ok(NbtTag::Int(v as i32))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<NbtTag> {
+        Ok(NbtTag::Long(v as i64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<NbtTag> {
+        Ok(NbtTag::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<NbtTag> {
+        Ok(NbtTag::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<NbtTag> {
+        Ok(NbtTag::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<NbtTag> {
+        Ok(NbtTag::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<NbtTag> {
+        Ok(NbtTag::ByteArray(v.iter().map(|&b| b as i8).collect()))
+    }
+
+    fn serialize_none(self) -> Result<NbtTag> {
+        Ok(NbtTag::End)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<NbtTag> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<NbtTag> {
+        Ok(NbtTag::End)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<NbtTag> {
+        Ok(NbtTag::compound())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<NbtTag> {
+        Ok(NbtTag::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<NbtTag> {
+        let tag = value.serialize(self)?;
+        match name {
+            BYTE_ARRAY_TOKEN => into_array(tag, 7),
+            INT_ARRAY_TOKEN => into_array(tag, 11),
+            LONG_ARRAY_TOKEN => into_array(tag, 12),
+            _ => Ok(tag),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<NbtTag> {
+        let mut map = CompoundMap::new();
+        map.insert(variant.to_string(), value.serialize(self)?);
+        Ok(NbtTag::Compound(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer> {
+        Ok(ListSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<ListSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<ListSerializer>> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<CompoundSerializer> {
+        Ok(CompoundSerializer {
+            map: CompoundMap::with_capacity(len.unwrap_or(0)),
+            pending_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<CompoundSerializer> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<CompoundSerializer>> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+// Convert a serialized sequence into a typed array tag
+fn into_array(tag: NbtTag, array_type: u8) -> Result<NbtTag> {
+    let values = match tag {
+        NbtTag::List { items, .. } => items,
+        NbtTag::ByteArray(v) if array_type == 7 => return Ok(NbtTag::ByteArray(v)),
+        NbtTag::IntArray(v) if array_type == 11 => return Ok(NbtTag::IntArray(v)),
+        NbtTag::LongArray(v) if array_type == 12 => return Ok(NbtTag::LongArray(v)),
+        tag => {
+            return Err(NbtError::Serde(format!(
+                "Cannot serialize {} as {}",
+                tag.type_name(),
+                tag_type_name(array_type)
+            )))
+        }
+    };
+
+    let mut longs = Vec::with_capacity(values.len());
+    for value in &values {
+        longs.push(match value {
+            NbtTag::Byte(v) => *v as i64,
+            NbtTag::Short(v) => *v as i64,
+            NbtTag::Int(v) => *v as i64,
+            NbtTag::Long(v) => *v,
+            tag => {
+                return Err(NbtError::Serde(format!(
+                    "Cannot store {} in {}",
+                    tag.type_name(),
+                    tag_type_name(array_type)
+                )))
+            }
+        });
+    }
+
+    Ok(match array_type {
+        7 => NbtTag::ByteArray(longs.into_iter().map(|v| v as i8).collect()),
+        11 => NbtTag::IntArray(longs.into_iter().map(|v| v as i32).collect()),
+        _ => NbtTag::LongArray(longs),
+    })
+}
+
+/// Collects sequence elements into a homogeneous `NbtTag::List`
+pub struct ListSerializer {
+    items: Vec<NbtTag>,
+}
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let item = value.serialize(TagSerializer)?;
+        if item == NbtTag::End {
+            return Err(NbtError::Serde("Cannot store None in a List".to_string()));
+        }
+        if let Some(first) = self.items.first() {
+            if first.type_id() != item.type_id() {
+                return Err(NbtError::ListTypeMismatch {
+                    expected: first.type_id(),
+                    found: item.type_id(),
+                });
+            }
+        }
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn finish(self) -> NbtTag {
+        let tag_type = self.items.first().map_or(0, |item| item.type_id());
+        NbtTag::List {
+            tag_type,
+            items: self.items,
+        }
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(self.finish())
+    }
+}
+
+/// Collects map and struct entries into an `NbtTag::Compound`
+pub struct CompoundSerializer {
+    map: CompoundMap,
+    pending_key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn insert(&mut self, key: String, value: NbtTag) {
+        if value != NbtTag::End {
+            self.map.insert(key, value);
+        }
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        let key = match key.serialize(TagSerializer)? {
+            NbtTag::String(s) => s,
+            NbtTag::Byte(v) => v.to_string(),
+            NbtTag::Short(v) => v.to_string(),
+            NbtTag::Int(v) => v.to_string(),
+            NbtTag::Long(v) => v.to_string(),
+            tag => {
+                return Err(NbtError::Serde(format!(
+                    "Compound keys must be strings, found {}",
+                    tag.type_name()
+                )))
+            }
+        };
+        self.pending_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| NbtError::Serde("Value serialized before key".to_string()))?;
+        let value = value.serialize(TagSerializer)?;
+        self.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(NbtTag::Compound(self.map))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let value = value.serialize(TagSerializer)?;
+        self.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(NbtTag::Compound(self.map))
+    }
+}
+
+/// Wraps a tuple or struct variant as `{variant: value}`
+pub struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &'static str, value: NbtTag) -> NbtTag {
+        let mut map = CompoundMap::new();
+        map.insert(variant.to_string(), value);
+        NbtTag::Compound(map)
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(Self::wrap(self.variant, self.inner.finish()))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<CompoundSerializer> {
+    type Ok = NbtTag;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<NbtTag> {
+        Ok(Self::wrap(self.variant, NbtTag::Compound(self.inner.map)))
+    }
+}
+
+impl Serialize for NbtTag {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            NbtTag::End => serializer.serialize_unit(),
+            NbtTag::Byte(v) => serializer.serialize_i8(*v),
+            NbtTag::Short(v) => serializer.serialize_i16(*v),
+            NbtTag::Int(v) => serializer.serialize_i32(*v),
+            NbtTag::Long(v) => serializer.serialize_i64(*v),
+            NbtTag::Float(v) => serializer.serialize_f32(*v),
+            NbtTag::Double(v) => serializer.serialize_f64(*v),
+            NbtTag::ByteArray(v) => serializer.serialize_newtype_struct(BYTE_ARRAY_TOKEN, v),
+            NbtTag::String(s) => serializer.serialize_str(s),
+            NbtTag::List { items, .. } => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            NbtTag::Compound(map) => {
+                let mut compound = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    compound.serialize_entry(key, value)?;
+                }
+                compound.end()
+            }
+            NbtTag::IntArray(v) => serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, v),
+            NbtTag::LongArray(v) => serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, v),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::ser::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Store an integer sequence as `ByteArray` instead of `List`
+///
+/// `#[serde(with = "nbt::byte_array")]`
+pub mod byte_array {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(BYTE_ARRAY_TOKEN, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Store an integer sequence as `IntArray` instead of `List`
+///
+/// `#[serde(with = "nbt::int_array")]`
+pub mod int_array {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Store an integer sequence as `LongArray` instead of `List`
+///
+/// `#[serde(with = "nbt::long_array")]`
+pub mod long_array {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Store a `u128` UUID as `IntArray[4]`, most significant int first
+///
+/// `#[serde(with = "nbt::uuid")]`
+pub mod uuid {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, &crate::uuid_to_ints(*value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        let ints = <[i32; 4]>::deserialize(deserializer)?;
+        Ok(crate::uuid_from_ints(ints))
+    }
+}
@@ -1,5 +1,4 @@
-use crate::{NbtError, NbtTag, Result};
-use std::collections::HashMap;
+use crate::{CompoundMap, NbtError, NbtTag, Result};
 
 use winnow::{
     ascii::{digit1, space0},
@@ -64,7 +63,7 @@ fn parse_compound(input: &mut Input) -> PResult<NbtTag> {
 
     pairs
         .map(|entries: Vec<(String, NbtTag)>| {
-            let map: HashMap<String, NbtTag> = entries.into_iter().collect();
+            let map: CompoundMap = entries.into_iter().collect();
             NbtTag::Compound(map)
         })
         .parse_next(input)
@@ -204,7 +203,7 @@ fn parse_number(input: &mut Input) -> PResult<NbtTag> {
 }
 
 /// Parse number from string with suffix handling
-fn parse_number_from_str(s: &str) -> Result<NbtTag> {
+pub(crate) fn parse_number_from_str(s: &str) -> Result<NbtTag> {
     let s = s.trim();
 
     if s.is_empty() {
@@ -353,8 +352,8 @@ fn format_list(items: &[NbtTag], pretty: bool, indent: usize) -> String {
     }
 }
 
-fn format_compound(map: &HashMap<String, NbtTag>, pretty: bool, indent: usize) -> String {
-    let mut entries: Vec<String> = map
+fn format_compound(map: &CompoundMap, pretty: bool, indent: usize) -> String {
+    let entries: Vec<String> = map
         .iter()
         .map(|(key, value)| {
             let formatted_value = format_tag_with_indent(value, indent + 1, pretty);
@@ -362,8 +361,6 @@ fn format_compound(map: &HashMap<String, NbtTag>, pretty: bool, indent: usize) -
         })
         .collect();
 
-    entries.sort();
-
     if pretty && entries.len() > 2 {
         let inner_indent = " ".repeat((indent + 1) * 4);
         let outer_indent = " ".repeat(indent * 4);
@@ -0,0 +1,571 @@
+use crate::snbt::parse_number_from_str;
+use crate::{format_snbt, tag_type_name, CompoundMap, NbtError, NbtTag, Result};
+use std::borrow::Cow;
+
+/// Byte range of a token in the SNBT input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// SNBT token produced by `SnbtLexer`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnbtToken<'a> {
+    /// `{`
+    CompoundStart,
+    /// `}`
+    CompoundEnd,
+    /// `[`
+    ListStart,
+    /// `[B;`, `[I;` or `[L;` with the NBT type of the array (7, 11 or 12)
+    ArrayStart(u8),
+    /// `]`
+    ListEnd,
+    /// `:`
+    Colon,
+    /// `,`
+    Comma,
+    /// Compound key (quoted or unquoted string followed by `:`)
+    Key(Cow<'a, str>),
+    /// Quoted or unquoted string value
+    String(Cow<'a, str>),
+    /// Typed number (`1b`, `2s`, `3`, `4L`, `5.0f`, `6.0d`)
+    Number(NbtTag),
+    /// `true` or `false`
+    Bool(bool),
+}
+
+/// Token with its position in the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken<'a> {
+    pub token: SnbtToken<'a>,
+    pub span: Span,
+}
+
+/// Single-pass SNBT lexer, suitable for syntax highlighting
+pub struct SnbtLexer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> SnbtLexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// Current byte offset in the input
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Skip the input that made `next_token` fail and return its span, so that callers
+    /// such as highlighters can keep lexing: the rest of a quoted string, otherwise one char
+    pub fn skip_invalid(&mut self) -> Span {
+        self.skip_whitespace();
+        let start = self.pos;
+        let bytes = self.input.as_bytes();
+        self.pos = match bytes.get(start) {
+            Some(&quote) if quote == b'"' || quote == b'\'' => {
+                let mut i = start + 1;
+                loop {
+                    match bytes.get(i) {
+                        None => break bytes.len(),
+                        Some(b'\\') => i += 2,
+                        Some(&b) if b == quote => break i + 1,
+                        Some(_) => i += 1,
+                    }
+                }
+            }
+            Some(_) => start + self.input[start..].chars().next().map_or(0, char::len_utf8),
+            None => start,
+        };
+        Span {
+            start,
+            end: self.pos,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len()
+            - rest
+                .trim_start_matches(|c: char| c.is_ascii_whitespace())
+                .len();
+    }
+
+    fn peek_non_whitespace(&self, from: usize) -> Option<(usize, u8)> {
+        self.input.as_bytes()[from..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .map(|offset| (from + offset, self.input.as_bytes()[from + offset]))
+    }
+
+    /// Read the next token, `None` at end of input
+    pub fn next_token(&mut self) -> Result<Option<SpannedToken<'a>>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let Some(&byte) = self.input.as_bytes().get(start) else {
+            return Ok(None);
+        };
+
+        let token = match byte {
+            b'{' => self.single(SnbtToken::CompoundStart),
+            b'}' => self.single(SnbtToken::CompoundEnd),
+            b']' => self.single(SnbtToken::ListEnd),
+            b':' => self.single(SnbtToken::Colon),
+            b',' => self.single(SnbtToken::Comma),
+            b'[' => self.lex_bracket(),
+            b'"' | b'\'' => {
+                let value = self.lex_quoted(byte)?;
+                if self.followed_by_colon() {
+                    SnbtToken::Key(value)
+                } else {
+                    SnbtToken::String(value)
+                }
+            }
+            _ if is_unquoted_char(self.input[start..].chars().next().unwrap()) => {
+                self.lex_unquoted()
+            }
+            _ => {
+                return Err(NbtError::snbt_parse_error(
+                    format!(
+                        "Unexpected character '{}'",
+                        self.input[start..].chars().next().unwrap()
+                    ),
+                    start,
+                ))
+            }
+        };
+
+        Ok(Some(SpannedToken {
+            token,
+            span: Span {
+                start,
+                end: self.pos,
+            },
+        }))
+    }
+
+    fn single(&mut self, token: SnbtToken<'a>) -> SnbtToken<'a> {
+        self.pos += 1;
+        token
+    }
+
+    fn lex_bracket(&mut self) -> SnbtToken<'a> {
+        self.pos += 1;
+        if let Some((prefix_pos, prefix)) = self.peek_non_whitespace(self.pos) {
+            let array_type = match prefix {
+                b'B' => Some(7),
+                b'I' => Some(11),
+                b'L' => Some(12),
+                _ => None,
+            };
+            if let Some(array_type) = array_type {
+                if let Some((semicolon_pos, b';')) = self.peek_non_whitespace(prefix_pos + 1) {
+                    self.pos = semicolon_pos + 1;
+                    return SnbtToken::ArrayStart(array_type);
+                }
+            }
+        }
+        SnbtToken::ListStart
+    }
+
+    fn lex_quoted(&mut self, quote: u8) -> Result<Cow<'a, str>> {
+        let start = self.pos;
+        let content_start = start + 1;
+        let bytes = self.input.as_bytes();
+        let mut owned: Option<String> = None;
+        let mut segment_start = content_start;
+        let mut i = content_start;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => {
+                    let Some(&escaped) = bytes.get(i + 1) else {
+                        break;
+                    };
+                    if escaped != b'\\' && escaped != b'"' && escaped != b'\'' {
+                        return Err(NbtError::snbt_parse_error(
+                            format!("Invalid escape sequence '\\{}'", escaped as char),
+                            i,
+                        ));
+                    }
+                    let buffer = owned.get_or_insert_with(String::new);
+                    buffer.push_str(&self.input[segment_start..i]);
+                    buffer.push(escaped as char);
+                    i += 2;
+                    segment_start = i;
+                }
+                b if b == quote => {
+                    self.pos = i + 1;
+                    return Ok(match owned {
+                        Some(mut buffer) => {
+                            buffer.push_str(&self.input[segment_start..i]);
+                            Cow::Owned(buffer)
+                        }
+                        None => Cow::Borrowed(&self.input[content_start..i]),
+                    });
+                }
+                _ => i += 1,
+            }
+        }
+
+        Err(NbtError::snbt_parse_error(
+            "Unterminated quoted string",
+            start,
+        ))
+    }
+
+    fn lex_unquoted(&mut self) -> SnbtToken<'a> {
+        let start = self.pos;
+        let len = self.input[start..]
+            .find(|c: char| !is_unquoted_char(c))
+            .unwrap_or(self.input.len() - start);
+        self.pos += len;
+        let word = &self.input[start..self.pos];
+
+        if self.followed_by_colon() {
+            return SnbtToken::Key(Cow::Borrowed(word));
+        }
+
+        // Same precedence as `parse_snbt`: number, then boolean, then string
+        if let Ok(number) = parse_number_from_str(word) {
+            return SnbtToken::Number(number);
+        }
+        if word.eq_ignore_ascii_case("true") {
+            return SnbtToken::Bool(true);
+        }
+        if word.eq_ignore_ascii_case("false") {
+            return SnbtToken::Bool(false);
+        }
+        SnbtToken::String(Cow::Borrowed(word))
+    }
+
+    fn followed_by_colon(&self) -> bool {
+        matches!(self.peek_non_whitespace(self.pos), Some((_, b':')))
+    }
+}
+
+impl<'a> Iterator for SnbtLexer<'a> {
+    type Item = Result<SpannedToken<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_token() {
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => None,
+            Err(e) => {
+                // Stop after the first error
+                self.pos = self.input.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+/// Structural event produced by `SnbtPullParser`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnbtEvent<'a> {
+    StartCompound,
+    EndCompound,
+    Key(Cow<'a, str>),
+    StartList,
+    EndList,
+    /// Start of a typed array with its NBT type (7, 11 or 12)
+    StartArray(u8),
+    EndArray,
+    /// Scalar value (number, string or boolean as Byte)
+    Value(NbtTag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Compound,
+    List,
+    Array(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    ValueOrEnd,
+    Key,
+    KeyOrEnd,
+    Colon,
+    CommaOrEnd,
+    Done,
+}
+
+/// Incremental SNBT parser emitting events without building the tree
+///
+/// Uses an explicit stack, so deeply nested input never overflows
+/// and every byte is read exactly once.
+pub struct SnbtPullParser<'a> {
+    lexer: SnbtLexer<'a>,
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl<'a> SnbtPullParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            lexer: SnbtLexer::new(input),
+            stack: Vec::new(),
+            expect: Expect::Value,
+        }
+    }
+
+    /// Current nesting depth
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Read the next event, `None` once the root value is complete
+    pub fn next_event(&mut self) -> Result<Option<(SnbtEvent<'a>, Span)>> {
+        loop {
+            let Some(SpannedToken { token, span }) = self.lexer.next_token()? else {
+                if self.expect == Expect::Done {
+                    return Ok(None);
+                }
+                return Err(NbtError::snbt_parse_error(
+                    "Unexpected end of input",
+                    self.lexer.position(),
+                ));
+            };
+
+            let event = match token {
+                SnbtToken::Colon if self.expect == Expect::Colon => {
+                    self.expect = Expect::Value;
+                    continue;
+                }
+                SnbtToken::Comma if self.expect == Expect::CommaOrEnd => {
+                    self.expect = match self.stack.last() {
+                        Some(Container::Compound) => Expect::Key,
+                        _ => Expect::Value,
+                    };
+                    continue;
+                }
+                SnbtToken::Key(key) if matches!(self.expect, Expect::Key | Expect::KeyOrEnd) => {
+                    self.expect = Expect::Colon;
+                    SnbtEvent::Key(key)
+                }
+                SnbtToken::CompoundEnd
+                    if self.top_is(Container::Compound)
+                        && matches!(self.expect, Expect::KeyOrEnd | Expect::CommaOrEnd) =>
+                {
+                    self.stack.pop();
+                    self.after_value();
+                    SnbtEvent::EndCompound
+                }
+                SnbtToken::ListEnd
+                    if matches!(self.expect, Expect::ValueOrEnd | Expect::CommaOrEnd) =>
+                {
+                    let event = match self.stack.pop() {
+                        Some(Container::Array(_)) => SnbtEvent::EndArray,
+                        _ => SnbtEvent::EndList,
+                    };
+                    self.after_value();
+                    event
+                }
+                token if matches!(self.expect, Expect::Value | Expect::ValueOrEnd) => {
+                    self.value_event(token, span)?
+                }
+                token => {
+                    return Err(NbtError::snbt_parse_error(
+                        format!("Unexpected {}", describe_token(&token)),
+                        span.start,
+                    ))
+                }
+            };
+
+            return Ok(Some((event, span)));
+        }
+    }
+
+    fn value_event(&mut self, token: SnbtToken<'a>, span: Span) -> Result<SnbtEvent<'a>> {
+        if let Some(Container::Array(array_type)) = self.stack.last().copied() {
+            return match token {
+                SnbtToken::Number(number)
+                    if !matches!(number, NbtTag::Float(_) | NbtTag::Double(_)) =>
+                {
+                    self.after_value();
+                    Ok(SnbtEvent::Value(number))
+                }
+                token => Err(NbtError::snbt_parse_error(
+                    format!(
+                        "Unexpected {} in {}",
+                        describe_token(&token),
+                        tag_type_name(array_type)
+                    ),
+                    span.start,
+                )),
+            };
+        }
+
+        let event = match token {
+            SnbtToken::CompoundStart => {
+                self.stack.push(Container::Compound);
+                self.expect = Expect::KeyOrEnd;
+                return Ok(SnbtEvent::StartCompound);
+            }
+            SnbtToken::ListStart => {
+                self.stack.push(Container::List);
+                self.expect = Expect::ValueOrEnd;
+                return Ok(SnbtEvent::StartList);
+            }
+            SnbtToken::ArrayStart(array_type) => {
+                self.stack.push(Container::Array(array_type));
+                self.expect = Expect::ValueOrEnd;
+                return Ok(SnbtEvent::StartArray(array_type));
+            }
+            SnbtToken::String(value) => SnbtEvent::Value(NbtTag::String(value.into_owned())),
+            SnbtToken::Number(number) => SnbtEvent::Value(number),
+            SnbtToken::Bool(value) => SnbtEvent::Value(NbtTag::Byte(value as i8)),
+            token => {
+                return Err(NbtError::snbt_parse_error(
+                    format!("Unexpected {}", describe_token(&token)),
+                    span.start,
+                ))
+            }
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    fn top_is(&self, container: Container) -> bool {
+        self.stack.last() == Some(&container)
+    }
+
+    fn after_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Done
+        } else {
+            Expect::CommaOrEnd
+        };
+    }
+}
+
+fn describe_token(token: &SnbtToken) -> String {
+    match token {
+        SnbtToken::CompoundStart => "'{'".to_string(),
+        SnbtToken::CompoundEnd => "'}'".to_string(),
+        SnbtToken::ListStart | SnbtToken::ArrayStart(_) => "'['".to_string(),
+        SnbtToken::ListEnd => "']'".to_string(),
+        SnbtToken::Colon => "':'".to_string(),
+        SnbtToken::Comma => "','".to_string(),
+        SnbtToken::Key(key) => format!("key '{key}'"),
+        SnbtToken::String(value) => format!("string '{value}'"),
+        SnbtToken::Number(number) => format!("{} value", number.type_name()),
+        SnbtToken::Bool(value) => format!("boolean '{value}'"),
+    }
+}
+
+enum Partial {
+    Compound(CompoundMap, Option<String>),
+    List(Vec<NbtTag>),
+    Array(u8, Vec<i64>),
+}
+
+/// Parse SNBT string to NBT tag with the non-recursive pull parser
+pub fn parse_snbt_streaming(input: &str) -> Result<NbtTag> {
+    let mut parser = SnbtPullParser::new(input);
+    let mut stack: Vec<Partial> = Vec::new();
+    let mut root = None;
+
+    while let Some((event, span)) = parser.next_event()? {
+        let value = match event {
+            SnbtEvent::StartCompound => {
+                stack.push(Partial::Compound(CompoundMap::new(), None));
+                continue;
+            }
+            SnbtEvent::StartList => {
+                stack.push(Partial::List(Vec::new()));
+                continue;
+            }
+            SnbtEvent::StartArray(array_type) => {
+                stack.push(Partial::Array(array_type, Vec::new()));
+                continue;
+            }
+            SnbtEvent::Key(key) => {
+                if let Some(Partial::Compound(_, pending)) = stack.last_mut() {
+                    *pending = Some(key.into_owned());
+                }
+                continue;
+            }
+            SnbtEvent::Value(tag) => tag,
+            SnbtEvent::EndCompound | SnbtEvent::EndList | SnbtEvent::EndArray => {
+                match stack.pop() {
+                    Some(Partial::Compound(map, _)) => NbtTag::Compound(map),
+                    Some(Partial::List(items)) => build_list(items)?,
+                    Some(Partial::Array(array_type, items)) => build_array(array_type, items),
+                    None => unreachable!("pull parser validates nesting"),
+                }
+            }
+        };
+
+        match stack.last_mut() {
+            Some(Partial::Compound(map, pending)) => {
+                if let Some(key) = pending.take() {
+                    map.insert(key, value);
+                }
+            }
+            Some(Partial::List(items)) => items.push(value),
+            Some(Partial::Array(array_type, values)) => {
+                let value = array_element(*array_type, &value).ok_or_else(|| {
+                    NbtError::snbt_parse_error(
+                        format!(
+                            "Invalid {} element {}",
+                            tag_type_name(*array_type),
+                            format_snbt(&value)
+                        ),
+                        span.start,
+                    )
+                })?;
+                values.push(value);
+            }
+            None => root = Some(value),
+        }
+    }
+
+    root.ok_or_else(|| NbtError::snbt_parse_error("Unexpected end of input", input.len()))
+}
+
+fn build_list(items: Vec<NbtTag>) -> Result<NbtTag> {
+    let tag_type = items.first().map_or(0, |item| item.type_id());
+    if let Some(item) = items.iter().find(|item| item.type_id() != tag_type) {
+        return Err(NbtError::SnbtListTypeMismatch {
+            expected: tag_type_name(tag_type).to_string(),
+            found: item.type_name().to_string(),
+        });
+    }
+    Ok(NbtTag::List { tag_type, items })
+}
+
+// Integer value of an array element, `None` for other tags and values out of the element range
+fn array_element(array_type: u8, tag: &NbtTag) -> Option<i64> {
+    let value = match tag {
+        NbtTag::Byte(v) => *v as i64,
+        NbtTag::Short(v) => *v as i64,
+        NbtTag::Int(v) => *v as i64,
+        NbtTag::Long(v) => *v,
+        _ => return None,
+    };
+    let fits = match array_type {
+        7 => i8::try_from(value).is_ok(),
+        11 => i32::try_from(value).is_ok(),
+        _ => true,
+    };
+    fits.then_some(value)
+}
+
+fn build_array(array_type: u8, values: Vec<i64>) -> NbtTag {
+    match array_type {
+        7 => NbtTag::ByteArray(values.into_iter().map(|v| v as i8).collect()),
+        11 => NbtTag::IntArray(values.into_iter().map(|v| v as i32).collect()),
+        _ => NbtTag::LongArray(values),
+    }
+}
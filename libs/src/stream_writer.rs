@@ -0,0 +1,408 @@
+use crate::{CompoundMap, Endian, NbtError, NbtTag, Result};
+use std::io::Write;
+
+// Stack buffer size for encoding int and long arrays
+const CHUNK_BYTES: usize = 4096;
+
+enum Frame {
+    Compound,
+    List { element_type: u8, remaining: usize },
+}
+
+/// Incremental NBT writer over any `io::Write`.
+///
+/// Values inside a compound are preceded by `key`; list elements are written
+/// directly and must match the type and length given to `begin_list`.
+/// Wrap unbuffered sinks (files, sockets) in a `BufWriter`.
+pub struct NbtStreamWriter<W: Write> {
+    inner: W,
+    endian: Endian,
+    stack: Vec<Frame>,
+    pending_key: Option<String>,
+    named_root: bool,
+    root_written: bool,
+}
+
+impl<W: Write> NbtStreamWriter<W> {
+    /// Writer for a named root tag (files, region chunks): call `key` before the root value
+    pub fn new(inner: W, endian: Endian) -> Self {
+        Self {
+            inner,
+            endian,
+            stack: Vec::new(),
+            pending_key: None,
+            named_root: true,
+            root_written: false,
+        }
+    }
+
+    /// Writer for a root tag without name (network NBT since 1.20.2)
+    pub fn unnamed(inner: W, endian: Endian) -> Self {
+        Self {
+            named_root: false,
+            ..Self::new(inner, endian)
+        }
+    }
+
+    /// Number of open compounds and lists
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Name of the next value (compound entry or named root)
+    pub fn key(&mut self, name: &str) -> Result<()> {
+        let allowed = match self.stack.last() {
+            Some(Frame::Compound) => true,
+            Some(Frame::List { .. }) => false,
+            None => self.named_root && !self.root_written,
+        };
+        if !allowed || self.pending_key.is_some() {
+            return Err(state_error(format!("unexpected key \"{name}\"")));
+        }
+        self.pending_key = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Start the root compound with its name
+    pub fn begin_root(&mut self, name: &str) -> Result<()> {
+        self.key(name)?;
+        self.begin_compound()
+    }
+
+    pub fn begin_compound(&mut self) -> Result<()> {
+        self.begin_value(10)?;
+        self.stack.push(Frame::Compound);
+        Ok(())
+    }
+
+    pub fn begin_list(&mut self, element_type: u8, len: usize) -> Result<()> {
+        if element_type > 12 {
+            return Err(NbtError::InvalidTagType(element_type));
+        }
+        if element_type == 0 && len > 0 {
+            return Err(state_error("non-empty list of End tags"));
+        }
+        self.begin_value(9)?;
+        self.write_u8(element_type)?;
+        self.write_len(len)?;
+        self.stack.push(Frame::List {
+            element_type,
+            remaining: len,
+        });
+        Ok(())
+    }
+
+    /// Close the innermost compound or list
+    pub fn end(&mut self) -> Result<()> {
+        if self.pending_key.is_some() {
+            return Err(state_error("key without value"));
+        }
+        match self.stack.pop() {
+            Some(Frame::Compound) => self.write_u8(0),
+            Some(Frame::List { remaining: 0, .. }) => Ok(()),
+            Some(Frame::List { remaining, .. }) => Err(state_error(format!(
+                "list closed with {remaining} missing elements"
+            ))),
+            None => Err(state_error("nothing to end")),
+        }
+    }
+
+    pub fn write_byte(&mut self, value: i8) -> Result<()> {
+        self.begin_value(1)?;
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_short(&mut self, value: i16) -> Result<()> {
+        self.begin_value(2)?;
+        self.write_i16(value)
+    }
+
+    pub fn write_int(&mut self, value: i32) -> Result<()> {
+        self.begin_value(3)?;
+        self.write_i32(value)
+    }
+
+    pub fn write_long(&mut self, value: i64) -> Result<()> {
+        self.begin_value(4)?;
+        self.write_i64(value)
+    }
+
+    pub fn write_float(&mut self, value: f32) -> Result<()> {
+        self.begin_value(5)?;
+        self.write_i32(value.to_bits() as i32)
+    }
+
+    pub fn write_double(&mut self, value: f64) -> Result<()> {
+        self.begin_value(6)?;
+        self.write_i64(value.to_bits() as i64)
+    }
+
+    pub fn write_string(&mut self, value: &str) -> Result<()> {
+        self.begin_value(8)?;
+        self.write_str(value)
+    }
+
+    pub fn write_byte_array(&mut self, values: &[i8]) -> Result<()> {
+        self.begin_value(7)?;
+        self.put_byte_array(values)
+    }
+
+    pub fn write_int_array(&mut self, values: &[i32]) -> Result<()> {
+        self.begin_value(11)?;
+        self.put_int_array(values)
+    }
+
+    pub fn write_long_array(&mut self, values: &[i64]) -> Result<()> {
+        self.begin_value(12)?;
+        self.put_long_array(values)
+    }
+
+    /// Write a complete tag as the next value
+    ///
+    /// The tag is streamed to the inner writer without being buffered. It is checked first,
+    /// so nothing is written for a tag with invalid lists or strings.
+    pub fn write_tag(&mut self, tag: &NbtTag) -> Result<()> {
+        if matches!(tag, NbtTag::End) {
+            return Err(state_error("End tag is not a value"));
+        }
+        check_tag(tag)?;
+        self.begin_value(tag.type_id())?;
+        self.write_payload(tag)
+    }
+
+    /// Write a compound entry (`key` followed by `write_tag`)
+    pub fn write_field(&mut self, name: &str, tag: &NbtTag) -> Result<()> {
+        self.key(name)?;
+        self.write_tag(tag)
+    }
+
+    /// Check that the root is complete, flush and return the inner writer
+    pub fn finish(mut self) -> Result<W> {
+        if !self.root_written || !self.stack.is_empty() || self.pending_key.is_some() {
+            return Err(state_error("root tag is not complete"));
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    // Write the type and name (or check the list element type) before a payload
+    fn begin_value(&mut self, tag_type: u8) -> Result<()> {
+        match self.stack.last_mut() {
+            Some(Frame::Compound) => {
+                let name = self
+                    .pending_key
+                    .take()
+                    .ok_or_else(|| state_error("compound entry without key"))?;
+                self.write_u8(tag_type)?;
+                self.write_str(&name)
+            }
+            Some(Frame::List {
+                element_type,
+                remaining,
+            }) => {
+                if *element_type != tag_type {
+                    return Err(NbtError::ListTypeMismatch {
+                        expected: *element_type,
+                        found: tag_type,
+                    });
+                }
+                if *remaining == 0 {
+                    return Err(state_error("more elements than the declared list length"));
+                }
+                *remaining -= 1;
+                Ok(())
+            }
+            None => {
+                if self.root_written {
+                    return Err(state_error("root tag already written"));
+                }
+                self.root_written = true;
+                self.write_u8(tag_type)?;
+                if self.named_root {
+                    let name = self
+                        .pending_key
+                        .take()
+                        .ok_or_else(|| state_error("root tag without name"))?;
+                    self.write_str(&name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Payload of a checked tag, written without list and key bookkeeping
+    fn write_payload(&mut self, tag: &NbtTag) -> Result<()> {
+        match tag {
+            NbtTag::End => Ok(()),
+            NbtTag::Byte(v) => self.write_u8(*v as u8),
+            NbtTag::Short(v) => self.write_i16(*v),
+            NbtTag::Int(v) => self.write_i32(*v),
+            NbtTag::Long(v) => self.write_i64(*v),
+            NbtTag::Float(v) => self.write_i32(v.to_bits() as i32),
+            NbtTag::Double(v) => self.write_i64(v.to_bits() as i64),
+            NbtTag::ByteArray(values) => self.put_byte_array(values),
+            NbtTag::String(value) => self.write_str(value),
+            NbtTag::List { tag_type, items } => {
+                self.write_u8(*tag_type)?;
+                self.write_len(items.len())?;
+                items.iter().try_for_each(|item| self.write_payload(item))
+            }
+            NbtTag::Compound(map) => {
+                for (name, value) in map {
+                    self.write_u8(value.type_id())?;
+                    self.write_str(name)?;
+                    self.write_payload(value)?;
+                }
+                self.write_u8(0)
+            }
+            NbtTag::IntArray(values) => self.put_int_array(values),
+            NbtTag::LongArray(values) => self.put_long_array(values),
+        }
+    }
+
+    fn put_byte_array(&mut self, values: &[i8]) -> Result<()> {
+        self.write_len(values.len())?;
+        // SAFETY: i8 and u8 have the same size and alignment, and every bit pattern is valid
+        let bytes =
+            unsafe { std::slice::from_raw_parts(values.as_ptr().cast::<u8>(), values.len()) };
+        self.inner.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn put_int_array(&mut self, values: &[i32]) -> Result<()> {
+        self.write_len(values.len())?;
+        let encode: fn(i32) -> [u8; 4] = match self.endian {
+            Endian::Big => i32::to_be_bytes,
+            Endian::Little => i32::to_le_bytes,
+        };
+        self.put_chunked(values, encode)
+    }
+
+    fn put_long_array(&mut self, values: &[i64]) -> Result<()> {
+        self.write_len(values.len())?;
+        let encode: fn(i64) -> [u8; 8] = match self.endian {
+            Endian::Big => i64::to_be_bytes,
+            Endian::Little => i64::to_le_bytes,
+        };
+        self.put_chunked(values, encode)
+    }
+
+    // Encodes through a fixed stack buffer, so large arrays are never copied whole
+    fn put_chunked<T: Copy, const N: usize>(
+        &mut self,
+        values: &[T],
+        encode: fn(T) -> [u8; N],
+    ) -> Result<()> {
+        let mut buffer = [0u8; CHUNK_BYTES];
+        for chunk in values.chunks(CHUNK_BYTES / N) {
+            for (bytes, value) in buffer.chunks_exact_mut(N).zip(chunk) {
+                bytes.copy_from_slice(&encode(*value));
+            }
+            self.inner.write_all(&buffer[..chunk.len() * N])?;
+        }
+        Ok(())
+    }
+
+    fn write_u8(&mut self, value: u8) -> Result<()> {
+        self.inner.write_all(&[value])?;
+        Ok(())
+    }
+
+    fn write_i16(&mut self, value: i16) -> Result<()> {
+        let bytes = match self.endian {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        };
+        self.inner.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn write_i32(&mut self, value: i32) -> Result<()> {
+        let bytes = match self.endian {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        };
+        self.inner.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn write_i64(&mut self, value: i64) -> Result<()> {
+        let bytes = match self.endian {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        };
+        self.inner.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<()> {
+        let len = i32::try_from(len).map_err(|_| state_error("length exceeds i32::MAX"))?;
+        self.write_i32(len)
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<()> {
+        let len =
+            u16::try_from(value.len()).map_err(|_| NbtError::InvalidStringLength(value.len()))?;
+        self.write_i16(len as i16)?;
+        self.inner.write_all(value.as_bytes())?;
+        Ok(())
+    }
+}
+
+// Everything `write_payload` can fail on besides I/O, checked before writing anything
+fn check_tag(tag: &NbtTag) -> Result<()> {
+    match tag {
+        NbtTag::String(value) => check_str(value),
+        NbtTag::List { tag_type, items } => {
+            if *tag_type == 0 && !items.is_empty() {
+                return Err(NbtError::InvalidTagType(0));
+            }
+            if let Some(item) = items.iter().find(|item| item.type_id() != *tag_type) {
+                return Err(NbtError::ListTypeMismatch {
+                    expected: *tag_type,
+                    found: item.type_id(),
+                });
+            }
+            check_len(items.len())?;
+            items.iter().try_for_each(check_tag)
+        }
+        NbtTag::Compound(map) => check_compound(map),
+        NbtTag::ByteArray(values) => check_len(values.len()),
+        NbtTag::IntArray(values) => check_len(values.len()),
+        NbtTag::LongArray(values) => check_len(values.len()),
+        _ => Ok(()),
+    }
+}
+
+fn check_compound(map: &CompoundMap) -> Result<()> {
+    for (name, value) in map {
+        if matches!(value, NbtTag::End) {
+            return Err(NbtError::InvalidTagType(0));
+        }
+        check_str(name)?;
+        check_tag(value)?;
+    }
+    Ok(())
+}
+
+fn check_str(value: &str) -> Result<()> {
+    match u16::try_from(value.len()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(NbtError::InvalidStringLength(value.len())),
+    }
+}
+
+fn check_len(len: usize) -> Result<()> {
+    match i32::try_from(len) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(state_error("length exceeds i32::MAX")),
+    }
+}
+
+fn state_error(message: impl Into<String>) -> NbtError {
+    NbtError::WriterState(message.into())
+}
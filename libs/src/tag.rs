@@ -1,4 +1,7 @@
-use crate::HashMap;
+use crate::{CompoundBuilder, FromNbt, NbtError, Result};
+
+/// Map backing `NbtTag::Compound`, keeps insertion (file) order
+pub type CompoundMap = indexmap::IndexMap<String, NbtTag>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum NbtTag {
@@ -12,7 +15,7 @@ pub enum NbtTag {
     ByteArray(Vec<i8>),
     String(String),
     List { tag_type: u8, items: Vec<NbtTag> },
-    Compound(HashMap<String, NbtTag>),
+    Compound(CompoundMap),
     IntArray(Vec<i32>),
     LongArray(Vec<i64>),
 }
@@ -23,11 +26,46 @@ impl NbtTag {
         NbtTag::Byte(value)
     }
 
+    // Create NBT Short Tags
+    pub fn short(value: i16) -> Self {
+        NbtTag::Short(value)
+    }
+
     // Create NBT Int Tags
     pub fn int(value: i32) -> Self {
         NbtTag::Int(value)
     }
 
+    // Create NBT Long Tags
+    pub fn long(value: i64) -> Self {
+        NbtTag::Long(value)
+    }
+
+    // Create NBT Float Tags
+    pub fn float(value: f32) -> Self {
+        NbtTag::Float(value)
+    }
+
+    // Create NBT Double Tags
+    pub fn double(value: f64) -> Self {
+        NbtTag::Double(value)
+    }
+
+    // Create NBT ByteArray Tags
+    pub fn byte_array(values: impl Into<Vec<i8>>) -> Self {
+        NbtTag::ByteArray(values.into())
+    }
+
+    // Create NBT IntArray Tags
+    pub fn int_array(values: impl Into<Vec<i32>>) -> Self {
+        NbtTag::IntArray(values.into())
+    }
+
+    // Create NBT LongArray Tags
+    pub fn long_array(values: impl Into<Vec<i64>>) -> Self {
+        NbtTag::LongArray(values.into())
+    }
+
     // Create NBT String Tags
     pub fn string(value: impl Into<String>) -> Self {
         NbtTag::String(value.into())
@@ -35,7 +73,12 @@ impl NbtTag {
 
     // Create NBT Compound Tags
     pub fn compound() -> Self {
-        NbtTag::Compound(HashMap::new())
+        NbtTag::Compound(CompoundMap::new())
+    }
+
+    // Start building a compound
+    pub fn builder() -> CompoundBuilder {
+        CompoundBuilder::new()
     }
 
     // Create NBT List Tags
@@ -46,6 +89,15 @@ impl NbtTag {
         }
     }
 
+    // Create a list from items of a single type (element type End when empty)
+    pub fn list_of(items: Vec<NbtTag>) -> Result<Self> {
+        let mut tag_type = 0;
+        for (i, item) in items.iter().enumerate() {
+            check_element(&mut tag_type, i, item)?;
+        }
+        Ok(NbtTag::List { tag_type, items })
+    }
+
     // Get NBT type ID (same as TypeScript NbtType enum)
     pub fn type_id(&self) -> u8 {
         match self {
@@ -65,6 +117,11 @@ impl NbtTag {
         }
     }
 
+    // Get NBT type name (used in error messages)
+    pub fn type_name(&self) -> &'static str {
+        tag_type_name(self.type_id())
+    }
+
     // Get as number (0 if not numeric)
     pub fn as_number(&self) -> f64 {
         match self {
@@ -87,7 +144,7 @@ impl NbtTag {
     }
 
     // Get as compound (None if not compound)
-    pub fn as_compound(&self) -> Option<&HashMap<String, NbtTag>> {
+    pub fn as_compound(&self) -> Option<&CompoundMap> {
         match self {
             NbtTag::Compound(map) => Some(map),
             _ => None,
@@ -95,7 +152,7 @@ impl NbtTag {
     }
 
     // Get mutable compound
-    pub fn as_compound_mut(&mut self) -> Option<&mut HashMap<String, NbtTag>> {
+    pub fn as_compound_mut(&mut self) -> Option<&mut CompoundMap> {
         match self {
             NbtTag::Compound(map) => Some(map),
             _ => None,
@@ -118,6 +175,53 @@ impl NbtTag {
         }
     }
 
+    // Append to a list, checking the element type (an empty list takes the type of its first element)
+    pub fn push(&mut self, item: NbtTag) -> Result<()> {
+        let (tag_type, items) = self.list_parts_mut()?;
+        check_element(tag_type, items.len(), &item)?;
+        items.push(item);
+        Ok(())
+    }
+
+    // Insert into a list at index, checking the element type
+    pub fn insert_at(&mut self, index: usize, item: NbtTag) -> Result<()> {
+        let (tag_type, items) = self.list_parts_mut()?;
+        if index > items.len() {
+            return Err(out_of_bounds(index, items.len()));
+        }
+        check_element(tag_type, items.len(), &item)?;
+        items.insert(index, item);
+        Ok(())
+    }
+
+    // Replace a list element, checking the element type; returns the previous element
+    pub fn set_at(&mut self, index: usize, item: NbtTag) -> Result<NbtTag> {
+        let (tag_type, items) = self.list_parts_mut()?;
+        if index >= items.len() {
+            return Err(out_of_bounds(index, items.len()));
+        }
+        check_element(tag_type, items.len(), &item)?;
+        Ok(std::mem::replace(&mut items[index], item))
+    }
+
+    // Remove a list element; a list left empty goes back to element type End
+    pub fn remove_at(&mut self, index: usize) -> Result<NbtTag> {
+        let (tag_type, items) = self.list_parts_mut()?;
+        if index >= items.len() {
+            return Err(out_of_bounds(index, items.len()));
+        }
+        let item = items.remove(index);
+        if items.is_empty() {
+            *tag_type = 0;
+        }
+        Ok(item)
+    }
+
+    fn list_parts_mut(&mut self) -> Result<(&mut u8, &mut Vec<NbtTag>)> {
+        let found = self.type_name();
+        self.as_list_mut().ok_or_else(|| NbtError::type_mismatch("List", found))
+    }
+
     // Type checking methods - simple and fast
     pub fn is_number(&self) -> bool {
         matches!(
@@ -151,6 +255,66 @@ impl NbtTag {
         self.as_compound()?.get(key)
     }
 
+    // Remove a key (compound only), keeping the order of the remaining keys
+    pub fn remove(&mut self, key: &str) -> Option<NbtTag> {
+        self.as_compound_mut()?.shift_remove(key)
+    }
+
+    // Get mutable nested value by key (compound only)
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtTag> {
+        self.as_compound_mut()?.get_mut(key)
+    }
+
+    // Get nested value by key, failing if self is not a compound or the key is missing
+    pub fn try_get(&self, key: &str) -> Result<&NbtTag> {
+        match self {
+            NbtTag::Compound(map) => map.get(key).ok_or_else(|| missing_key(key)),
+            other => Err(NbtError::type_mismatch("Compound", other.type_name())),
+        }
+    }
+
+    // Get mutable nested value by key, failing if self is not a compound or the key is missing
+    pub fn try_get_mut(&mut self, key: &str) -> Result<&mut NbtTag> {
+        match self {
+            NbtTag::Compound(map) => map.get_mut(key).ok_or_else(|| missing_key(key)),
+            other => Err(NbtError::type_mismatch("Compound", other.type_name())),
+        }
+    }
+
+    // Convert the value at key, errors are prefixed with the key path
+    pub fn try_get_as<T: FromNbt>(&self, key: &str) -> Result<T> {
+        T::from_nbt(self.try_get(key)?).map_err(|e| e.at_key(key))
+    }
+
+    // Get a list by key as Vec<T> (e.g. try_get_list_of::<CompoundMap>("Items"))
+    pub fn try_get_list_of<T: FromNbt>(&self, key: &str) -> Result<Vec<T>> {
+        match self.try_get(key)? {
+            list @ NbtTag::List { .. } => Vec::<T>::from_nbt(list).map_err(|e| e.at_key(key)),
+            other => Err(NbtError::type_mismatch("List", other.type_name()).at_key(key)),
+        }
+    }
+
+    // Get list items by key
+    pub fn try_get_list(&self, key: &str) -> Result<&[NbtTag]> {
+        match self.try_get(key)? {
+            NbtTag::List { items, .. } => Ok(items),
+            other => Err(mismatch_at(key, "List", other)),
+        }
+    }
+
+    // Get the list tag by key, to mutate it with the checked push/insert_at/set_at/remove_at
+    pub fn try_get_list_mut(&mut self, key: &str) -> Result<&mut NbtTag> {
+        match self.try_get_mut(key)? {
+            list @ NbtTag::List { .. } => Ok(list),
+            other => Err(mismatch_at(key, "List", other)),
+        }
+    }
+
+    // Get boolean by key (Byte only, != 0)
+    pub fn try_get_bool(&self, key: &str) -> Result<bool> {
+        self.try_get_i8(key).map(|v| v != 0)
+    }
+
     // Get string value by key
     pub fn get_string(&self, key: &str) -> &str {
         self.get(key).map(|tag| tag.as_string()).unwrap_or("")
@@ -167,7 +331,106 @@ impl NbtTag {
     }
 
     // Get compound by key
-    pub fn get_compound(&self, key: &str) -> Option<&HashMap<String, NbtTag>> {
+    pub fn get_compound(&self, key: &str) -> Option<&CompoundMap> {
         self.get(key)?.as_compound()
     }
 }
+
+// Checked accessors: a missing key or another tag type is an error naming the key
+macro_rules! typed_accessors {
+    ($($kind:ident $variant:ident => $get:ident($ret:ty), $get_mut:ident($ret_mut:ty);)*) => {
+        impl NbtTag {
+            $(
+                pub fn $get(&self, key: &str) -> Result<$ret> {
+                    match self.try_get(key)? {
+                        NbtTag::$variant(v) => Ok(typed_accessors!(@$kind v)),
+                        other => Err(mismatch_at(key, stringify!($variant), other)),
+                    }
+                }
+
+                pub fn $get_mut(&mut self, key: &str) -> Result<$ret_mut> {
+                    match self.try_get_mut(key)? {
+                        NbtTag::$variant(v) => Ok(v),
+                        other => Err(mismatch_at(key, stringify!($variant), other)),
+                    }
+                }
+            )*
+        }
+    };
+    // Numbers are returned by value, strings, arrays and compounds by reference
+    (@copy $v:ident) => {
+        *$v
+    };
+    (@borrow $v:ident) => {
+        $v
+    };
+}
+
+typed_accessors! {
+    copy Byte => try_get_i8(i8), try_get_i8_mut(&mut i8);
+    copy Short => try_get_i16(i16), try_get_i16_mut(&mut i16);
+    copy Int => try_get_i32(i32), try_get_i32_mut(&mut i32);
+    copy Long => try_get_i64(i64), try_get_i64_mut(&mut i64);
+    copy Float => try_get_f32(f32), try_get_f32_mut(&mut f32);
+    copy Double => try_get_f64(f64), try_get_f64_mut(&mut f64);
+    borrow String => try_get_str(&str), try_get_string_mut(&mut String);
+    borrow ByteArray => try_get_byte_array(&[i8]), try_get_byte_array_mut(&mut Vec<i8>);
+    borrow IntArray => try_get_int_array(&[i32]), try_get_int_array_mut(&mut Vec<i32>);
+    borrow LongArray => try_get_long_array(&[i64]), try_get_long_array_mut(&mut Vec<i64>);
+    borrow Compound => try_get_compound(&CompoundMap), try_get_compound_mut(&mut CompoundMap);
+}
+
+fn missing_key(key: &str) -> NbtError {
+    NbtError::MissingKey {
+        path: String::new(),
+    }
+    .at_key(key)
+}
+
+fn mismatch_at(key: &str, expected: &str, found: &NbtTag) -> NbtError {
+    NbtError::type_mismatch(expected, found.type_name()).at_key(key)
+}
+
+// Check a list element against the element type; lists with no elements accept any type
+fn check_element(tag_type: &mut u8, len: usize, item: &NbtTag) -> Result<()> {
+    let found = item.type_id();
+    if found == 0 {
+        return Err(NbtError::InvalidTagType(0));
+    }
+    if len == 0 {
+        *tag_type = found;
+    } else if *tag_type != found {
+        return Err(NbtError::ListTypeMismatch {
+            expected: *tag_type,
+            found,
+        });
+    }
+    Ok(())
+}
+
+fn out_of_bounds(index: usize, len: usize) -> NbtError {
+    NbtError::IndexOutOfBounds {
+        index: index as i64,
+        len,
+    }
+}
+
+// Get NBT type name from a type ID
+pub fn tag_type_name(tag_type: u8) -> &'static str {
+    match tag_type {
+        0 => "End",
+        1 => "Byte",
+        2 => "Short",
+        3 => "Int",
+        4 => "Long",
+        5 => "Float",
+        6 => "Double",
+        7 => "ByteArray",
+        8 => "String",
+        9 => "List",
+        10 => "Compound",
+        11 => "IntArray",
+        12 => "LongArray",
+        _ => "Unknown",
+    }
+}
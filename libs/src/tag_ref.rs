@@ -0,0 +1,438 @@
+use crate::{CompoundMap, Endian, IntArrayRef, LongArrayRef, NbtError, NbtReader, NbtTag, Result};
+use std::borrow::Cow;
+use std::cell::OnceCell;
+
+/// String borrowed from NBT data, kept as raw (Modified) UTF-8 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NbtStr<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> NbtStr<'a> {
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The string if it is valid UTF-8 (true for anything without NUL or characters outside the BMP)
+    pub fn to_str(&self) -> Option<&'a str> {
+        std::str::from_utf8(self.bytes).ok()
+    }
+
+    /// Decode as Java's Modified UTF-8, borrowing when the bytes are plain UTF-8
+    pub fn decode(&self) -> Cow<'a, str> {
+        match self.to_str() {
+            Some(s) => Cow::Borrowed(s),
+            None => Cow::Owned(decode_mutf8(self.bytes)),
+        }
+    }
+}
+
+/// Borrowed view over binary NBT.
+///
+/// Strings and arrays point into the input buffer; compounds and lists are decoded lazily,
+/// when iterated or on the first lookup. Use `to_tag` to get an `NbtTag`.
+#[derive(Debug, Clone)]
+pub enum NbtTagRef<'a> {
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [u8]),
+    String(NbtStr<'a>),
+    List(ListRef<'a>),
+    Compound(CompoundRef<'a>),
+    IntArray(IntArrayRef<'a>),
+    LongArray(LongArrayRef<'a>),
+}
+
+impl<'a> NbtTagRef<'a> {
+    /// Parse a named root tag, validating the whole tree once without allocating
+    pub fn parse(data: &'a [u8], endian: Endian) -> Result<(NbtStr<'a>, NbtTagRef<'a>)> {
+        let mut reader = NbtReader::new(data, endian);
+        let tag_type = reader.read_u8()?;
+        let name = read_nbt_str(&mut reader)?;
+        let start = reader.cursor;
+        reader.skip_tag(tag_type)?;
+        Ok((name, read_value(data, start, tag_type, endian)?.0))
+    }
+
+    /// Parse a root tag without name (network NBT since 1.20.2)
+    pub fn parse_unnamed(data: &'a [u8], endian: Endian) -> Result<NbtTagRef<'a>> {
+        let mut reader = NbtReader::new(data, endian);
+        let tag_type = reader.read_u8()?;
+        let start = reader.cursor;
+        reader.skip_tag(tag_type)?;
+        Ok(read_value(data, start, tag_type, endian)?.0)
+    }
+
+    // Get NBT type ID (same as NbtTag::type_id)
+    pub fn type_id(&self) -> u8 {
+        match self {
+            NbtTagRef::End => 0,
+            NbtTagRef::Byte(_) => 1,
+            NbtTagRef::Short(_) => 2,
+            NbtTagRef::Int(_) => 3,
+            NbtTagRef::Long(_) => 4,
+            NbtTagRef::Float(_) => 5,
+            NbtTagRef::Double(_) => 6,
+            NbtTagRef::ByteArray(_) => 7,
+            NbtTagRef::String(_) => 8,
+            NbtTagRef::List(_) => 9,
+            NbtTagRef::Compound(_) => 10,
+            NbtTagRef::IntArray(_) => 11,
+            NbtTagRef::LongArray(_) => 12,
+        }
+    }
+
+    // Get as number (0 if not numeric)
+    pub fn as_number(&self) -> f64 {
+        match self {
+            NbtTagRef::Byte(v) => *v as f64,
+            NbtTagRef::Short(v) => *v as f64,
+            NbtTagRef::Int(v) => *v as f64,
+            NbtTagRef::Long(v) => *v as f64,
+            NbtTagRef::Float(v) => *v as f64,
+            NbtTagRef::Double(v) => *v,
+            _ => 0.0,
+        }
+    }
+
+    // Get as UTF-8 string (None if not a string or not valid UTF-8)
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            NbtTagRef::String(s) => s.to_str(),
+            _ => None,
+        }
+    }
+
+    // Get as compound view
+    pub fn as_compound(&self) -> Option<&CompoundRef<'a>> {
+        match self {
+            NbtTagRef::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    // Get as list view
+    pub fn as_list(&self) -> Option<&ListRef<'a>> {
+        match self {
+            NbtTagRef::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    // Get nested value by key (compound only)
+    pub fn get(&self, key: &str) -> Option<&NbtTagRef<'a>> {
+        self.as_compound()?.get(key)
+    }
+
+    /// Copy into an owned `NbtTag`
+    pub fn to_tag(&self) -> NbtTag {
+        match self {
+            NbtTagRef::End => NbtTag::End,
+            NbtTagRef::Byte(v) => NbtTag::Byte(*v),
+            NbtTagRef::Short(v) => NbtTag::Short(*v),
+            NbtTagRef::Int(v) => NbtTag::Int(*v),
+            NbtTagRef::Long(v) => NbtTag::Long(*v),
+            NbtTagRef::Float(v) => NbtTag::Float(*v),
+            NbtTagRef::Double(v) => NbtTag::Double(*v),
+            NbtTagRef::ByteArray(bytes) => {
+                NbtTag::ByteArray(bytes.iter().map(|b| *b as i8).collect())
+            }
+            NbtTagRef::String(s) => NbtTag::String(s.decode().into_owned()),
+            NbtTagRef::List(list) => NbtTag::List {
+                tag_type: list.element_type(),
+                items: list.iter().map(|item| item.to_tag()).collect(),
+            },
+            NbtTagRef::Compound(compound) => NbtTag::Compound(
+                compound
+                    .iter()
+                    .map(|(key, value)| (key.decode().into_owned(), value.to_tag()))
+                    .collect::<CompoundMap>(),
+            ),
+            NbtTagRef::IntArray(array) => NbtTag::IntArray(array.to_vec()),
+            NbtTagRef::LongArray(array) => NbtTag::LongArray(array.to_vec()),
+        }
+    }
+}
+
+impl From<&NbtTagRef<'_>> for NbtTag {
+    fn from(tag: &NbtTagRef<'_>) -> Self {
+        tag.to_tag()
+    }
+}
+
+/// Lazily decoded compound; the entry index is built on the first lookup
+#[derive(Debug, Clone)]
+pub struct CompoundRef<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    index: OnceCell<Vec<(NbtStr<'a>, NbtTagRef<'a>)>>,
+}
+
+impl<'a> CompoundRef<'a> {
+    /// Entries in file order, decoded on the fly
+    pub fn iter(&self) -> CompoundIter<'a> {
+        CompoundIter {
+            data: self.data,
+            cursor: 0,
+            endian: self.endian,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtTagRef<'a>> {
+        self.entries()
+            .iter()
+            .find(|(name, _)| name.as_bytes() == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.first().is_none_or(|tag_type| *tag_type == 0)
+    }
+
+    /// Indexed entries in file order
+    pub fn entries(&self) -> &[(NbtStr<'a>, NbtTagRef<'a>)] {
+        self.index.get_or_init(|| self.iter().collect())
+    }
+}
+
+/// Iterator over the entries of a `CompoundRef`
+pub struct CompoundIter<'a> {
+    data: &'a [u8],
+    cursor: usize,
+    endian: Endian,
+}
+
+impl<'a> Iterator for CompoundIter<'a> {
+    type Item = (NbtStr<'a>, NbtTagRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut reader = NbtReader::new(self.data, self.endian);
+        reader.cursor = self.cursor;
+        let tag_type = reader.read_u8().ok()?;
+        if tag_type == 0 {
+            return None;
+        }
+        let name = read_nbt_str(&mut reader).ok()?;
+        let (value, end) = read_value(self.data, reader.cursor, tag_type, self.endian).ok()?;
+        self.cursor = end;
+        Some((name, value))
+    }
+}
+
+/// Lazily decoded list; elements of variable size are indexed on the first lookup
+#[derive(Debug, Clone)]
+pub struct ListRef<'a> {
+    element_type: u8,
+    len: usize,
+    data: &'a [u8],
+    endian: Endian,
+    index: OnceCell<Vec<NbtTagRef<'a>>>,
+}
+
+impl<'a> ListRef<'a> {
+    pub fn element_type(&self) -> u8 {
+        self.element_type
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<NbtTagRef<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        match fixed_size(self.element_type) {
+            Some(size) => read_value(self.data, index * size, self.element_type, self.endian)
+                .ok()
+                .map(|(value, _)| value),
+            None => self
+                .index
+                .get_or_init(|| self.iter().collect())
+                .get(index)
+                .cloned(),
+        }
+    }
+
+    /// Elements in order, decoded on the fly
+    pub fn iter(&self) -> ListIter<'a> {
+        ListIter {
+            data: self.data,
+            cursor: 0,
+            remaining: self.len,
+            element_type: self.element_type,
+            endian: self.endian,
+        }
+    }
+}
+
+/// Iterator over the elements of a `ListRef`
+pub struct ListIter<'a> {
+    data: &'a [u8],
+    cursor: usize,
+    remaining: usize,
+    element_type: u8,
+    endian: Endian,
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = NbtTagRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (value, end) =
+            read_value(self.data, self.cursor, self.element_type, self.endian).ok()?;
+        self.cursor = end;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+fn fixed_size(tag_type: u8) -> Option<usize> {
+    match tag_type {
+        1 => Some(1),
+        2 => Some(2),
+        3 | 5 => Some(4),
+        4 | 6 => Some(8),
+        _ => None,
+    }
+}
+
+fn read_nbt_str<'a>(reader: &mut NbtReader<'a>) -> Result<NbtStr<'a>> {
+    let len = reader.read_i16()? as u16 as usize;
+    Ok(NbtStr {
+        bytes: reader.read_bytes(len)?,
+    })
+}
+
+// Decode the value at `start`, returning it with the offset just past it
+fn read_value<'a>(
+    data: &'a [u8],
+    start: usize,
+    tag_type: u8,
+    endian: Endian,
+) -> Result<(NbtTagRef<'a>, usize)> {
+    let mut reader = NbtReader::new(data, endian);
+    reader.cursor = start;
+    let value = match tag_type {
+        0 => NbtTagRef::End,
+        1 => NbtTagRef::Byte(reader.read_i8()?),
+        2 => NbtTagRef::Short(reader.read_i16()?),
+        3 => NbtTagRef::Int(reader.read_i32()?),
+        4 => NbtTagRef::Long(reader.read_i64()?),
+        5 => NbtTagRef::Float(reader.read_f32()?),
+        6 => NbtTagRef::Double(reader.read_f64()?),
+        7 => NbtTagRef::ByteArray(reader.read_array_bytes(1)?),
+        8 => NbtTagRef::String(read_nbt_str(&mut reader)?),
+        9 => {
+            let element_type = reader.read_u8()?;
+            let len = reader.read_len()?;
+            let body = reader.cursor;
+            for _ in 0..len {
+                reader.skip_tag(element_type)?;
+            }
+            NbtTagRef::List(ListRef {
+                element_type,
+                len,
+                data: &data[body..reader.cursor],
+                endian,
+                index: OnceCell::new(),
+            })
+        }
+        10 => {
+            let body = reader.cursor;
+            reader.skip_tag(10)?;
+            NbtTagRef::Compound(CompoundRef {
+                data: &data[body..reader.cursor],
+                endian,
+                index: OnceCell::new(),
+            })
+        }
+        11 => NbtTagRef::IntArray(IntArrayRef::new(reader.read_array_bytes(4)?, endian)),
+        12 => NbtTagRef::LongArray(LongArrayRef::new(reader.read_array_bytes(8)?, endian)),
+        _ => return Err(NbtError::InvalidTagType(tag_type)),
+    };
+    Ok((value, reader.cursor))
+}
+
+// Modified UTF-8: NUL as C0 80 and supplementary characters as surrogate pairs
+fn decode_mutf8(bytes: &[u8]) -> String {
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let continuation = |offset: usize| {
+            bytes
+                .get(i + offset)
+                .filter(|c| *c & 0xC0 == 0x80)
+                .map(|c| (*c & 0x3F) as u32)
+        };
+        if b < 0x80 {
+            units.push(b as u16);
+            i += 1;
+        } else if b & 0xE0 == 0xC0 {
+            match continuation(1) {
+                Some(c1) => {
+                    units.push(((b as u32 & 0x1F) << 6 | c1) as u16);
+                    i += 2;
+                }
+                None => {
+                    units.push(0xFFFD);
+                    i += 1;
+                }
+            }
+        } else if b & 0xF0 == 0xE0 {
+            match (continuation(1), continuation(2)) {
+                (Some(c1), Some(c2)) => {
+                    units.push(((b as u32 & 0x0F) << 12 | c1 << 6 | c2) as u16);
+                    i += 3;
+                }
+                _ => {
+                    units.push(0xFFFD);
+                    i += 1;
+                }
+            }
+        } else if b & 0xF8 == 0xF0 {
+            // Standard 4-byte UTF-8, tolerated in mixed input
+            match (continuation(1), continuation(2), continuation(3)) {
+                (Some(c1), Some(c2), Some(c3)) => {
+                    let code = (b as u32 & 0x07) << 18 | c1 << 12 | c2 << 6 | c3;
+                    let c = char::from_u32(code).unwrap_or('\u{FFFD}');
+                    units.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+                    i += 4;
+                }
+                _ => {
+                    units.push(0xFFFD);
+                    i += 1;
+                }
+            }
+        } else {
+            units.push(0xFFFD);
+            i += 1;
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
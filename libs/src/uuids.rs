@@ -0,0 +1,209 @@
+use crate::{CompoundMap, NbtError, NbtPath, NbtTag, Result};
+
+/// Pair names renamed by vanilla's 1.16 UUID fixes, e.g. projectile `OwnerUUIDMost` / `OwnerUUIDLeast`
+/// become `Owner`; other pairs keep their name (`UUIDMost` / `UUIDLeast` become `UUID`)
+pub const VANILLA_UUID_RENAMES: &[(&str, &str)] =
+    &[("OwnerUUID", "Owner"), ("DragonUUID", "Dragon")];
+
+/// UUID from the four ints of the `IntArray` form, most significant first
+pub fn uuid_from_ints(ints: [i32; 4]) -> u128 {
+    ints.iter()
+        .fold(0u128, |uuid, &int| (uuid << 32) | int as u32 as u128)
+}
+
+/// UUID as the four ints of the `IntArray` form, most significant first
+pub fn uuid_to_ints(uuid: u128) -> [i32; 4] {
+    [
+        (uuid >> 96) as i32,
+        (uuid >> 64) as i32,
+        (uuid >> 32) as i32,
+        uuid as i32,
+    ]
+}
+
+/// UUID from the `...Most` / `...Least` longs used before 1.16
+pub fn uuid_from_longs(most: i64, least: i64) -> u128 {
+    ((most as u64 as u128) << 64) | least as u64 as u128
+}
+
+/// UUID as its most and least significant longs
+pub fn uuid_to_longs(uuid: u128) -> (i64, i64) {
+    ((uuid >> 64) as i64, uuid as i64)
+}
+
+/// Hyphenated lowercase form, e.g. `f81d4fae-7dec-11d0-a765-00a0c91e6bf6`
+pub fn format_uuid(uuid: u128) -> String {
+    let hex = format!("{uuid:032x}");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Parse a hyphenated UUID, or 32 hex digits without hyphens
+///
+/// Like Java's `UUID.fromString`, hyphenated groups may be shorter than usual
+/// (`1-2-3-4-5`), which is how some older files store them.
+pub fn parse_uuid(s: &str) -> Result<u128> {
+    let invalid = || NbtError::InvalidString(format!("invalid UUID {s:?}"));
+    if !s.contains('-') {
+        if s.len() != 32 {
+            return Err(invalid());
+        }
+        return hex(s).ok_or_else(invalid);
+    }
+
+    let groups: Vec<&str> = s.split('-').collect();
+    let widths = [8, 4, 4, 4, 12];
+    if groups.len() != widths.len() {
+        return Err(invalid());
+    }
+    let mut uuid = 0u128;
+    for (group, width) in groups.into_iter().zip(widths) {
+        if group.is_empty() || group.len() > width {
+            return Err(invalid());
+        }
+        uuid = (uuid << (width * 4)) | hex(group).ok_or_else(invalid)?;
+    }
+    Ok(uuid)
+}
+
+fn hex(digits: &str) -> Option<u128> {
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u128::from_str_radix(digits, 16).ok()
+}
+
+impl NbtTag {
+    // UUID in the `IntArray[4]` form used since 1.16
+    pub fn from_uuid(uuid: u128) -> NbtTag {
+        NbtTag::IntArray(uuid_to_ints(uuid).to_vec())
+    }
+
+    // UUID stored in this tag: an `IntArray` of length 4 or a UUID string
+    pub fn as_uuid(&self) -> Option<u128> {
+        match self {
+            NbtTag::IntArray(ints) => ints.as_slice().try_into().ok().map(uuid_from_ints),
+            NbtTag::String(s) => parse_uuid(s).ok(),
+            _ => None,
+        }
+    }
+
+    // UUID under `key` in any of its forms: `key` as an int array or string,
+    // or the legacy `{key}Most` / `{key}Least` longs
+    pub fn try_get_uuid(&self, key: &str) -> Result<u128> {
+        let Some(value) = self.get(key) else {
+            if self.get(&format!("{key}Most")).is_none() {
+                return Err(NbtError::MissingKey {
+                    path: String::new(),
+                }
+                .at_key(key));
+            }
+            let most = self.try_get_i64(&format!("{key}Most"))?;
+            let least = self.try_get_i64(&format!("{key}Least"))?;
+            return Ok(uuid_from_longs(most, least));
+        };
+        match value {
+            NbtTag::IntArray(ints) => {
+                let ints: [i32; 4] = ints.as_slice().try_into().map_err(|_| {
+                    NbtError::type_mismatch("IntArray of length 4", "IntArray").at_key(key)
+                })?;
+                Ok(uuid_from_ints(ints))
+            }
+            NbtTag::String(s) => parse_uuid(s).map_err(|e| e.at_key(key)),
+            other => Err(NbtError::type_mismatch("IntArray", other.type_name()).at_key(key)),
+        }
+    }
+
+    // Store a UUID under `key` as an int array, dropping any legacy longs for it
+    // (compound only, returns false otherwise)
+    pub fn set_uuid(&mut self, key: &str, uuid: u128) -> bool {
+        if !self.is_compound() {
+            return false;
+        }
+        self.remove(&format!("{key}Most"));
+        self.remove(&format!("{key}Least"));
+        if let Some(map) = self.as_compound_mut() {
+            map.insert(key.to_string(), NbtTag::from_uuid(uuid));
+        }
+        true
+    }
+
+    // Rewrite every `{name}Most` / `{name}Least` long pair in the tree into an int array,
+    // renamed per `VANILLA_UUID_RENAMES`; returns the paths of the compounds that were changed.
+    // Only long pairs are rewritten, not string UUIDs or `{M, L}` compounds
+    pub fn migrate_uuids(&mut self) -> Vec<NbtPath> {
+        self.migrate_uuids_with(VANILLA_UUID_RENAMES)
+    }
+
+    // Like `migrate_uuids`, with `(pair name, new key)` renames; unlisted pairs keep their name.
+    // A pair is left alone when its new key already exists or another pair maps to it
+    pub fn migrate_uuids_with(&mut self, renames: &[(&str, &str)]) -> Vec<NbtPath> {
+        let mut changed = Vec::new();
+        // Compounds stay compounds, so only lists that were already mixed can fail the walk
+        let _ = self.walk_mut(&mut |path: &NbtPath, tag: &mut NbtTag| {
+            if let NbtTag::Compound(map) = tag {
+                if migrate_compound(map, renames) {
+                    changed.push(path.clone());
+                }
+            }
+            true
+        });
+        changed
+    }
+}
+
+// Rebuilds the map so the new key takes the place of the `Most` half
+fn migrate_compound(map: &mut CompoundMap, renames: &[(&str, &str)]) -> bool {
+    let mut pairs: Vec<(String, String, u128)> = map
+        .iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_suffix("Most").filter(|name| !name.is_empty())?;
+            let target = renames
+                .iter()
+                .find(|(from, _)| *from == name)
+                .map_or(name, |(_, to)| *to);
+            match (value, map.get(&format!("{name}Least"))) {
+                (NbtTag::Long(most), Some(NbtTag::Long(least))) if !map.contains_key(target) => {
+                    let uuid = uuid_from_longs(*most, *least);
+                    Some((name.to_string(), target.to_string(), uuid))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    // Pairs renamed onto the same key (`OwnerUUID` and `Owner`) would overwrite each other
+    let clashing: Vec<String> = pairs
+        .iter()
+        .filter(|(_, target, _)| pairs.iter().filter(|(_, other, _)| other == target).count() > 1)
+        .map(|(_, target, _)| target.clone())
+        .collect();
+    pairs.retain(|(_, target, _)| !clashing.contains(target));
+    if pairs.is_empty() {
+        return false;
+    }
+
+    let mut migrated = CompoundMap::new();
+    for (key, value) in std::mem::take(map) {
+        let pair = pairs.iter().find(|(name, _, _)| {
+            key.strip_prefix(name.as_str())
+                .is_some_and(|suffix| suffix == "Most" || suffix == "Least")
+        });
+        match pair {
+            Some((_, target, uuid)) if key.ends_with("Most") => {
+                migrated.insert(target.clone(), NbtTag::from_uuid(*uuid));
+            }
+            Some(_) => {}
+            None => {
+                migrated.insert(key, value);
+            }
+        }
+    }
+    *map = migrated;
+    true
+}
@@ -0,0 +1,260 @@
+use crate::{tag_type_name, NbtError, NbtPath, NbtTag, PathNode, Result};
+use std::borrow::Cow;
+
+/// Read-only visitor over a tag tree, see `NbtTag::walk`
+///
+/// Tags are visited parents first, with `path` pointing at the visited tag from the root.
+/// Elements of byte, int and long arrays are visited as `Byte`, `Int` and `Long` tags.
+pub trait Visitor {
+    /// Return `false` to skip the children of `tag`
+    fn visit(&mut self, path: &NbtPath, tag: &NbtTag) -> bool;
+}
+
+/// Mutating visitor over a tag tree, see `NbtTag::walk_mut`
+///
+/// Children are visited after their parent has been visited (and possibly replaced). Array
+/// elements are written back when they keep their type. A list takes the type of its elements
+/// once they have been visited (End when empty); elements of mixed types make `walk_mut` fail.
+pub trait VisitorMut {
+    /// Return `false` to skip the children of `tag`
+    fn visit_mut(&mut self, path: &NbtPath, tag: &mut NbtTag) -> bool;
+}
+
+impl<F: FnMut(&NbtPath, &NbtTag) -> bool> Visitor for F {
+    fn visit(&mut self, path: &NbtPath, tag: &NbtTag) -> bool {
+        self(path, tag)
+    }
+}
+
+impl<F: FnMut(&NbtPath, &mut NbtTag) -> bool> VisitorMut for F {
+    fn visit_mut(&mut self, path: &NbtPath, tag: &mut NbtTag) -> bool {
+        self(path, tag)
+    }
+}
+
+impl NbtTag {
+    // Visit every tag of the tree, parents first
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        walk_tag(&mut NbtPath::new(Vec::new()), self, &mut |path, tag| {
+            visitor.visit(path, tag)
+        });
+    }
+
+    // Visit every tag of the tree mutably, parents first; fails on the first list left
+    // with elements of mixed types, which stays as the visitor left it
+    pub fn walk_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) -> Result<()> {
+        walk_tag_mut(&mut NbtPath::new(Vec::new()), self, visitor)
+    }
+
+    // Remove compound entries, list elements and array elements rejected by `keep`, at any depth
+    pub fn retain(&mut self, mut keep: impl FnMut(&NbtPath, &NbtTag) -> bool) {
+        retain_children(&mut NbtPath::new(Vec::new()), self, &mut keep);
+    }
+
+    // Replace strings for which `f` returns a new value, at any depth
+    pub fn map_strings(&mut self, mut f: impl FnMut(&NbtPath, &str) -> Option<String>) {
+        // Strings stay strings, so only lists that were already mixed can fail the walk
+        let _ = self.walk_mut(&mut |path: &NbtPath, tag: &mut NbtTag| {
+            if let NbtTag::String(value) = tag {
+                if let Some(new) = f(path, value) {
+                    *value = new;
+                }
+            }
+            true
+        });
+    }
+
+    // All tags matching the predicate with their paths (array elements are returned as owned tags)
+    pub fn find_all(
+        &self,
+        mut predicate: impl FnMut(&NbtPath, &NbtTag) -> bool,
+    ) -> Vec<(NbtPath, Cow<'_, NbtTag>)> {
+        let mut found = Vec::new();
+        walk_tag(&mut NbtPath::new(Vec::new()), self, &mut |path, tag| {
+            if predicate(path, tag) {
+                found.push((path.clone(), tag.clone()));
+            }
+            true
+        });
+        found
+    }
+}
+
+fn walk_tag<'a, F>(path: &mut NbtPath, tag: &'a NbtTag, f: &mut F)
+where
+    F: FnMut(&NbtPath, &Cow<'a, NbtTag>) -> bool,
+{
+    if !f(path, &Cow::Borrowed(tag)) {
+        return;
+    }
+    match tag {
+        NbtTag::Compound(map) => {
+            for (key, value) in map {
+                path.push(PathNode::Child(key.clone()));
+                walk_tag(path, value, f);
+                path.pop();
+            }
+        }
+        NbtTag::List { items, .. } => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathNode::Index(i as i32));
+                walk_tag(path, item, f);
+                path.pop();
+            }
+        }
+        NbtTag::ByteArray(values) => walk_array(path, values, NbtTag::Byte, f),
+        NbtTag::IntArray(values) => walk_array(path, values, NbtTag::Int, f),
+        NbtTag::LongArray(values) => walk_array(path, values, NbtTag::Long, f),
+        _ => {}
+    }
+}
+
+fn walk_array<'a, T: Copy, F>(path: &mut NbtPath, values: &[T], wrap: fn(T) -> NbtTag, f: &mut F)
+where
+    F: FnMut(&NbtPath, &Cow<'a, NbtTag>) -> bool,
+{
+    for (i, value) in values.iter().enumerate() {
+        path.push(PathNode::Index(i as i32));
+        f(path, &Cow::Owned(wrap(*value)));
+        path.pop();
+    }
+}
+
+fn walk_tag_mut<V: VisitorMut + ?Sized>(
+    path: &mut NbtPath,
+    tag: &mut NbtTag,
+    visitor: &mut V,
+) -> Result<()> {
+    if !visitor.visit_mut(path, tag) {
+        return Ok(());
+    }
+    match tag {
+        NbtTag::Compound(map) => {
+            for (key, value) in map.iter_mut() {
+                path.push(PathNode::Child(key.clone()));
+                let result = walk_tag_mut(path, value, visitor);
+                path.pop();
+                result?;
+            }
+        }
+        NbtTag::List { tag_type, items } => {
+            for (i, item) in items.iter_mut().enumerate() {
+                path.push(PathNode::Index(i as i32));
+                let result = walk_tag_mut(path, item, visitor);
+                path.pop();
+                result?;
+            }
+            let first = items.first().map_or(0, NbtTag::type_id);
+            if let Some(i) = items.iter().position(|item| item.type_id() != first) {
+                path.push(PathNode::Index(i as i32));
+                let error = NbtError::TypeMismatch {
+                    path: path.to_string(),
+                    expected: tag_type_name(first).to_string(),
+                    found: items[i].type_name().to_string(),
+                };
+                path.pop();
+                return Err(error);
+            }
+            *tag_type = first;
+        }
+        NbtTag::ByteArray(values) => walk_array_mut(path, values, NbtTag::Byte, byte, visitor),
+        NbtTag::IntArray(values) => walk_array_mut(path, values, NbtTag::Int, int, visitor),
+        NbtTag::LongArray(values) => walk_array_mut(path, values, NbtTag::Long, long, visitor),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn walk_array_mut<T: Copy, V: VisitorMut + ?Sized>(
+    path: &mut NbtPath,
+    values: &mut [T],
+    wrap: fn(T) -> NbtTag,
+    unwrap: fn(&NbtTag) -> Option<T>,
+    visitor: &mut V,
+) {
+    for (i, value) in values.iter_mut().enumerate() {
+        let mut element = wrap(*value);
+        path.push(PathNode::Index(i as i32));
+        visitor.visit_mut(path, &mut element);
+        path.pop();
+        if let Some(new) = unwrap(&element) {
+            *value = new;
+        }
+    }
+}
+
+fn retain_children<F>(path: &mut NbtPath, tag: &mut NbtTag, keep: &mut F)
+where
+    F: FnMut(&NbtPath, &NbtTag) -> bool,
+{
+    match tag {
+        NbtTag::Compound(map) => map.retain(|key, value| {
+            path.push(PathNode::Child(key.clone()));
+            let kept = keep(path, value);
+            if kept {
+                retain_children(path, value, keep);
+            }
+            path.pop();
+            kept
+        }),
+        NbtTag::List { tag_type, items } => {
+            let mut i = 0;
+            items.retain_mut(|item| {
+                path.push(PathNode::Index(i));
+                let kept = keep(path, item);
+                if kept {
+                    retain_children(path, item, keep);
+                }
+                path.pop();
+                i += 1;
+                kept
+            });
+            if items.is_empty() {
+                *tag_type = 0;
+            }
+        }
+        NbtTag::ByteArray(values) => retain_array(path, values, NbtTag::Byte, keep),
+        NbtTag::IntArray(values) => retain_array(path, values, NbtTag::Int, keep),
+        NbtTag::LongArray(values) => retain_array(path, values, NbtTag::Long, keep),
+        _ => {}
+    }
+}
+
+fn retain_array<T: Copy, F>(
+    path: &mut NbtPath,
+    values: &mut Vec<T>,
+    wrap: fn(T) -> NbtTag,
+    keep: &mut F,
+) where
+    F: FnMut(&NbtPath, &NbtTag) -> bool,
+{
+    let mut i = 0;
+    values.retain(|value| {
+        path.push(PathNode::Index(i));
+        let kept = keep(path, &wrap(*value));
+        path.pop();
+        i += 1;
+        kept
+    });
+}
+
+fn byte(tag: &NbtTag) -> Option<i8> {
+    match tag {
+        NbtTag::Byte(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn int(tag: &NbtTag) -> Option<i32> {
+    match tag {
+        NbtTag::Int(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn long(tag: &NbtTag) -> Option<i64> {
+    match tag {
+        NbtTag::Long(v) => Some(*v),
+        _ => None,
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{NbtFile, NbtTag};
+use crate::{NbtFile, NbtTag, SnbtLexer, SnbtToken};
 use std::collections::HashMap;
 use std::sync::{LazyLock, Mutex};
 use wasm_bindgen::prelude::*;
@@ -373,3 +373,51 @@ pub fn nbt_region_dispose(handle: u32) {
     let mut store = REGION_STORE.lock().unwrap();
     store.regions.remove(&handle);
 }
+
+/// Tokenize SNBT for syntax highlighting.
+/// Returns flat `[kind, start, end, tag_type]` quadruples with UTF-16 offsets.
+/// Input the lexer rejects becomes an error token (kind 11) and lexing continues after it.
+#[wasm_bindgen]
+pub fn snbt_tokenize(input: &str) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut byte_pos = 0;
+    let mut utf16_pos = 0;
+    let mut to_utf16 = |offset: usize| {
+        utf16_pos += input[byte_pos..offset].encode_utf16().count() as u32;
+        byte_pos = offset;
+        utf16_pos
+    };
+
+    let mut lexer = SnbtLexer::new(input);
+    loop {
+        let token = match lexer.next_token() {
+            Ok(Some(token)) => token,
+            Ok(None) => break,
+            Err(_) => {
+                let span = lexer.skip_invalid();
+                let start = to_utf16(span.start);
+                let end = to_utf16(span.end);
+                result.extend_from_slice(&[11, start, end, 0]);
+                continue;
+            }
+        };
+        let (kind, tag_type) = match &token.token {
+            SnbtToken::CompoundStart => (0, 0),
+            SnbtToken::CompoundEnd => (1, 0),
+            SnbtToken::ListStart => (2, 0),
+            SnbtToken::ArrayStart(array_type) => (3, *array_type),
+            SnbtToken::ListEnd => (4, 0),
+            SnbtToken::Colon => (5, 0),
+            SnbtToken::Comma => (6, 0),
+            SnbtToken::Key(_) => (7, 8),
+            SnbtToken::String(_) => (8, 8),
+            SnbtToken::Number(number) => (9, number.type_id()),
+            SnbtToken::Bool(_) => (10, 1),
+        };
+        let start = to_utf16(token.span.start);
+        let end = to_utf16(token.span.end);
+        result.extend_from_slice(&[kind, start, end, tag_type as u32]);
+    }
+
+    result
+}
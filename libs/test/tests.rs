@@ -1,4 +1,7 @@
-use crate::{Endian, NbtReader, NbtTag, NbtWriter, HashMap};
+use crate::{CompoundMap, Endian, NbtReader, NbtTag, NbtWriter};
+#[cfg(feature = "derive")]
+use crate::NbtCompound;
+use crate::{parse_snbt, parse_snbt_streaming, SnbtEvent, SnbtLexer, SnbtPullParser, SnbtToken};
 
 #[test]
 fn test_basic_types() {
@@ -15,14 +18,14 @@ fn test_basic_types() {
     assert!(string_tag.is_string());
 
     // Test compound
-    let compound_tag = NbtTag::Compound(HashMap::new());
+    let compound_tag = NbtTag::Compound(CompoundMap::new());
     assert_eq!(compound_tag.type_id(), 10);
     assert!(compound_tag.is_compound());
 }
 
 #[test]
 fn test_compound_access() {
-    let mut map = HashMap::new();
+    let mut map = CompoundMap::new();
     map.insert("name".to_string(), NbtTag::String("Steve".to_string()));
     map.insert("level".to_string(), NbtTag::Int(42));
     
@@ -38,7 +41,7 @@ fn test_compound_access() {
 #[test]
 fn test_write_read_roundtrip() {
     // Create a compound like in TypeScript test: {foo: "Hello!"}
-    let mut map = HashMap::new();
+    let mut map = CompoundMap::new();
     map.insert("foo".to_string(), NbtTag::String("Hello!".to_string()));
     let original = NbtTag::Compound(map);
 
@@ -164,12 +167,12 @@ fn test_arrays() {
 #[test]
 fn test_nested_compounds() {
     // Test compound imbriqué comme dans Minecraft
-    let mut inner = HashMap::new();
+    let mut inner = CompoundMap::new();
     inner.insert("x".to_string(), NbtTag::Int(100));
     inner.insert("y".to_string(), NbtTag::Int(64));
     inner.insert("z".to_string(), NbtTag::Int(-200));
 
-    let mut outer = HashMap::new();
+    let mut outer = CompoundMap::new();
     outer.insert("position".to_string(), NbtTag::Compound(inner));
     outer.insert("name".to_string(), NbtTag::String("Player".to_string()));
     outer.insert("health".to_string(), NbtTag::Float(20.0));
@@ -224,20 +227,20 @@ fn test_complex_minecraft_structure() {
     let mut entities = Vec::new();
     
     // Zombie
-    let mut zombie = HashMap::new();
+    let mut zombie = CompoundMap::new();
     zombie.insert("id".to_string(), NbtTag::String("minecraft:zombie".to_string()));
     zombie.insert("Health".to_string(), NbtTag::Float(20.0));
     zombie.insert("Age".to_string(), NbtTag::Int(0));
     entities.push(NbtTag::Compound(zombie));
 
     // Player  
-    let mut player = HashMap::new();
+    let mut player = CompoundMap::new();
     player.insert("id".to_string(), NbtTag::String("minecraft:player".to_string()));
     player.insert("Health".to_string(), NbtTag::Float(20.0));
     player.insert("foodLevel".to_string(), NbtTag::Int(20));
     entities.push(NbtTag::Compound(player));
 
-    let mut root = HashMap::new();
+    let mut root = CompoundMap::new();
     root.insert("DataVersion".to_string(), NbtTag::Int(3210));
     root.insert("entities".to_string(), NbtTag::List {
         tag_type: 10, // Compound
@@ -271,4 +274,1593 @@ fn test_complex_minecraft_structure() {
     let parsed = reader.read_tag(10).unwrap();
 
     assert_eq!(parsed, nbt);
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_snbt_lexer_tokens() {
+    let input = r#"{Name:"Steve \"S\"",Pos:[1.0d,2.5f],Data:[I;1,2],ok:true}"#;
+    let tokens: Vec<SnbtToken> = SnbtLexer::new(input)
+        .map(|token| token.unwrap().token)
+        .collect();
+
+    assert_eq!(tokens[1], SnbtToken::Key("Name".into()));
+    assert_eq!(tokens[3], SnbtToken::String("Steve \"S\"".into()));
+    assert_eq!(tokens[8], SnbtToken::Number(NbtTag::Double(1.0)));
+    assert_eq!(tokens[10], SnbtToken::Number(NbtTag::Float(2.5)));
+    assert_eq!(tokens[15], SnbtToken::ArrayStart(11));
+    assert_eq!(tokens[23], SnbtToken::Bool(true));
+
+    // Spans point back into the input
+    let spanned = SnbtLexer::new(input).nth(15).unwrap().unwrap();
+    assert_eq!(&input[spanned.span.start..spanned.span.end], "[I;");
+
+    // Highlighting keeps going past invalid input: `@` and the bad escape become error tokens
+    let tokens = crate::wasm::snbt_tokenize(r#"{a:@,é:"x\q",b:1}"#);
+    let kinds: Vec<u32> = tokens.chunks(4).map(|token| token[0]).collect();
+    assert_eq!(kinds, [0, 7, 5, 11, 6, 7, 5, 11, 6, 7, 5, 9, 1]);
+    assert_eq!(&tokens[28..32], &[11, 7, 12, 0]);
+}
+
+#[test]
+fn test_snbt_pull_parser() {
+    let mut parser = SnbtPullParser::new("{a:[1b,2b],b:{}}");
+    let mut events = Vec::new();
+    while let Some((event, _)) = parser.next_event().unwrap() {
+        events.push(event);
+    }
+
+    assert_eq!(events.len(), 10);
+    assert_eq!(events[0], SnbtEvent::StartCompound);
+    assert_eq!(events[1], SnbtEvent::Key("a".into()));
+    assert_eq!(events[3], SnbtEvent::Value(NbtTag::Byte(1)));
+    assert_eq!(events[9], SnbtEvent::EndCompound);
+
+    assert!(SnbtPullParser::new("{a:1,}").next_event().is_ok());
+    assert!(parse_snbt_streaming("{a:1,}").is_err());
+    assert!(parse_snbt_streaming("[1,2b]").is_err());
+    assert!(parse_snbt_streaming("{a:1} b").is_err());
+
+    // Array elements must be integers in range
+    assert_eq!(parse_snbt_streaming("[B;127,-128b]").unwrap(), NbtTag::ByteArray(vec![127, -128]));
+    let error = parse_snbt_streaming("[B;1,300]").unwrap_err();
+    assert!(matches!(error, crate::NbtError::SnbtParse { position: 5, .. }), "{error}");
+    assert!(parse_snbt_streaming("[I;1.5f]").is_err());
+    assert!(parse_snbt_streaming("[I;2147483648L]").is_err());
+    assert!(parse_snbt_streaming("[L;\"x\"]").is_err());
+}
+
+#[test]
+fn test_snbt_streaming_matches_parse_snbt() {
+    let input = "{id:\"minecraft:stone\",Count:64b,Pos:[1.0d,64.0d,2.0d],Bytes:[B;1b,2b],Longs:[L;9000000000L],empty:[]}";
+    assert_eq!(parse_snbt_streaming(input).unwrap(), parse_snbt(input).unwrap());
+
+    // Deep nesting does not recurse
+    let deep = format!("{}{}", "[".repeat(10_000), "]".repeat(10_000));
+    assert!(parse_snbt_streaming(&deep).is_ok());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_mappings() {
+    use crate::JsonMapping;
+    use serde_json::json;
+
+    let value = json!({"name": "Steve", "level": 30, "big": 5000000000i64, "hp": 19.5, "alive": true, "pos": [1, 2.5]});
+
+    let natural = NbtTag::from_json(&value, JsonMapping::Natural).unwrap();
+    assert_eq!(natural.get("level"), Some(&NbtTag::Int(30)));
+    assert_eq!(natural.get("big"), Some(&NbtTag::Long(5000000000)));
+    assert_eq!(natural.get("alive"), Some(&NbtTag::Byte(1)));
+    assert_eq!(natural.get("pos").unwrap().as_list().unwrap().0, &6);
+    assert!(NbtTag::from_json(&json!([1, "a"]), JsonMapping::Natural).is_err());
+
+    let vanilla = NbtTag::from_json(&value, JsonMapping::Vanilla).unwrap();
+    assert_eq!(vanilla.get("level"), Some(&NbtTag::Double(30.0)));
+    assert_eq!(
+        NbtTag::from_json(&json!([true, false]), JsonMapping::Vanilla).unwrap(),
+        NbtTag::ByteArray(vec![1, 0])
+    );
+    let mixed = NbtTag::from_json(&json!([1, "a", {"b": 2}]), JsonMapping::Vanilla).unwrap();
+    let (tag_type, items) = mixed.as_list().unwrap();
+    assert_eq!(*tag_type, 10);
+    assert_eq!(items[1].get_string(""), "a");
+    assert_eq!(items[2].get_number("b"), 2.0);
+    assert_eq!(mixed.to_json(JsonMapping::Vanilla), json!([1.0, "a", {"b": 2.0}]));
+    assert_eq!(
+        NbtTag::from_json(&json!([]), JsonMapping::Vanilla).unwrap(),
+        NbtTag::list(0)
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_typed_roundtrip() {
+    use crate::JsonMapping;
+    use serde_json::json;
+
+    let mut map = CompoundMap::new();
+    map.insert("b".to_string(), NbtTag::Byte(1));
+    map.insert("f".to_string(), NbtTag::Float(f32::INFINITY));
+    map.insert("l".to_string(), NbtTag::LongArray(vec![i64::MAX]));
+    map.insert("list".to_string(), NbtTag::List { tag_type: 2, items: vec![NbtTag::Short(7)] });
+    let tag = NbtTag::Compound(map);
+
+    let typed = tag.to_json(JsonMapping::Typed);
+    assert_eq!(typed["value"]["b"], json!({"type": "byte", "value": 1}));
+    assert_eq!(typed["value"]["list"]["element_type"], json!("short"));
+    assert_eq!(NbtTag::from_json(&typed, JsonMapping::Typed).unwrap(), tag);
+
+    // Out of range values are errors, not wrapped
+    let overflow = json!({"type": "byte", "value": 300});
+    assert!(matches!(NbtTag::from_json(&overflow, JsonMapping::Typed), Err(crate::NbtError::Json(_))));
+    let overflow = json!({"type": "int_array", "value": [1, 4294967296i64]});
+    assert!(NbtTag::from_json(&overflow, JsonMapping::Typed).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_struct_roundtrip() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        id: String,
+        #[serde(rename = "Count")]
+        count: u8,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Player {
+        name: String,
+        flying: bool,
+        #[serde(with = "crate::int_array")]
+        scores: Vec<i32>,
+        #[serde(with = "crate::long_array")]
+        states: Vec<i64>,
+        #[serde(with = "crate::uuid")]
+        uuid: u128,
+        pos: Vec<f64>,
+        inventory: Vec<Item>,
+        nickname: Option<String>,
+        extra: NbtTag,
+    }
+
+    let player = Player {
+        name: "Steve".to_string(),
+        flying: true,
+        scores: vec![1, 2, 3],
+        states: vec![i64::MIN],
+        uuid: 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+        pos: vec![0.5, 64.0, -3.25],
+        inventory: vec![Item { id: "minecraft:stone".to_string(), count: 200 }],
+        nickname: None,
+        extra: NbtTag::IntArray(vec![7]),
+    };
+
+    let tag = crate::to_tag(&player).unwrap();
+    assert_eq!(tag.get("flying"), Some(&NbtTag::Byte(1)));
+    assert_eq!(tag.get("scores"), Some(&NbtTag::IntArray(vec![1, 2, 3])));
+    assert_eq!(tag.get("uuid").unwrap().type_id(), 11);
+    assert_eq!(tag.get("pos").unwrap().as_list().unwrap().0, &6);
+    assert!(tag.get("nickname").is_none());
+    assert_eq!(crate::from_tag::<Player>(&tag).unwrap(), player);
+
+    let bytes = crate::to_bytes(&player, "").unwrap();
+    assert_eq!(crate::from_bytes::<Player>(&bytes).unwrap(), player);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_unsigned_arrays() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Arrays {
+        #[serde(with = "crate::byte_array")]
+        bytes: Vec<u8>,
+        #[serde(with = "crate::int_array")]
+        ints: Vec<u32>,
+        #[serde(with = "crate::long_array")]
+        longs: Vec<u64>,
+    }
+
+    let arrays = Arrays { bytes: vec![1, 200], ints: vec![0xFFFF_FFFF], longs: vec![u64::MAX] };
+    let tag = crate::to_tag(&arrays).unwrap();
+    assert_eq!(tag.get("bytes"), Some(&NbtTag::ByteArray(vec![1, -56])));
+    assert_eq!(tag.get("ints"), Some(&NbtTag::IntArray(vec![-1])));
+    assert_eq!(crate::from_tag::<Arrays>(&tag).unwrap(), arrays);
+
+    let bytes = crate::to_bytes(&arrays, "").unwrap();
+    assert_eq!(crate::from_bytes::<Arrays>(&bytes).unwrap(), arrays);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_borrowed_strings() {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Block<'a> {
+        #[serde(rename = "Name")]
+        name: &'a str,
+        #[serde(borrow)]
+        palette: Vec<&'a str>,
+    }
+
+    let mut map = CompoundMap::new();
+    map.insert("Name".to_string(), NbtTag::string("minecraft:stone"));
+    map.insert("Ignored".to_string(), NbtTag::LongArray(vec![1, 2, 3]));
+    map.insert(
+        "palette".to_string(),
+        NbtTag::List { tag_type: 8, items: vec![NbtTag::string("a"), NbtTag::string("b")] },
+    );
+    let bytes = crate::to_bytes(&NbtTag::Compound(map), "root").unwrap();
+
+    let block: Block = crate::from_bytes(&bytes).unwrap();
+    assert_eq!(block.name, "minecraft:stone");
+    assert_eq!(block.palette, vec!["a", "b"]);
+    let name_ptr = block.name.as_ptr() as usize;
+    assert!(name_ptr >= bytes.as_ptr() as usize && name_ptr < bytes.as_ptr() as usize + bytes.len());
+
+    // Unread elements are an error in both deserializers
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Owner {
+        #[serde(with = "crate::uuid")]
+        id: u128,
+        after: i32,
+    }
+    let mut map = CompoundMap::new();
+    map.insert("id".to_string(), NbtTag::IntArray(vec![1, 2, 3, 4, 5]));
+    map.insert("after".to_string(), NbtTag::Int(1));
+    let tag = NbtTag::Compound(map);
+    let bytes = crate::to_bytes(&tag, "").unwrap();
+    let tree_error = crate::from_tag::<Owner>(&tag).unwrap_err().to_string();
+    let bytes_error = crate::from_bytes::<Owner>(&bytes).unwrap_err().to_string();
+    assert!(tree_error.contains("invalid length 5"), "{tree_error}");
+    assert_eq!(bytes_error, tree_error);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_compound_roundtrip() {
+    use crate::{FromNbt, NbtCompound, ToNbt};
+
+    #[derive(NbtCompound, Debug, PartialEq, Default)]
+    struct Position {
+        x: i32,
+        z: i32,
+    }
+
+    #[derive(NbtCompound, Debug, PartialEq)]
+    #[nbt(rename_all = "PascalCase")]
+    struct Entity {
+        custom_name: Option<String>,
+        #[nbt(rename = "id")]
+        kind: String,
+        #[nbt(default)]
+        health: f32,
+        #[nbt(default = "default_air")]
+        air: i16,
+        #[nbt(int_array, rename = "UUID")]
+        uuid: Vec<i32>,
+        #[nbt(flatten)]
+        position: Position,
+        tags: Vec<String>,
+        #[nbt(skip)]
+        cached: bool,
+    }
+
+    fn default_air() -> i16 {
+        300
+    }
+
+    let tag = parse_snbt(r#"{id:"minecraft:pig",UUID:[I;1,2,3,4],x:5,z:-2,Tags:["a"]}"#).unwrap();
+    let entity = Entity::from_nbt(&tag).unwrap();
+    assert_eq!(entity.custom_name, None);
+    assert_eq!(entity.kind, "minecraft:pig");
+    assert_eq!(entity.health, 0.0);
+    assert_eq!(entity.air, 300);
+    assert_eq!(entity.position, Position { x: 5, z: -2 });
+    assert!(!entity.cached);
+
+    let written = entity.to_nbt();
+    assert_eq!(written.get("UUID"), Some(&NbtTag::IntArray(vec![1, 2, 3, 4])));
+    assert_eq!(written.get("x"), Some(&NbtTag::Int(5)));
+    assert!(written.get("CustomName").is_none());
+    assert!(written.get("Cached").is_none());
+    assert_eq!(Entity::from_compound(written.as_compound().unwrap()).unwrap(), entity);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_error_paths() {
+    use crate::{FromNbt, NbtError};
+
+    #[derive(NbtCompound, Debug)]
+    struct Display {
+        #[nbt(rename = "Name")]
+        name: String,
+    }
+
+    #[derive(NbtCompound, Debug)]
+    struct ItemTag {
+        display: Display,
+    }
+
+    #[derive(NbtCompound, Debug)]
+    struct Item {
+        tag: Option<ItemTag>,
+    }
+
+    #[derive(NbtCompound, Debug)]
+    struct Player {
+        #[nbt(rename = "Inventory")]
+        inventory: Vec<Item>,
+    }
+
+    let tag = parse_snbt("{Inventory:[{},{},{},{tag:{display:{Name:3}}}]}").unwrap();
+    let err = Player::from_nbt(&tag).unwrap_err();
+    assert!(matches!(err, NbtError::TypeMismatch { .. }));
+    assert_eq!(err.to_string(), "Inventory[3].tag.display.Name: expected String, found Int");
+
+    let tag = parse_snbt("{Inventory:[{tag:{}}]}").unwrap();
+    let err = Player::from_nbt(&tag).unwrap_err();
+    assert_eq!(err.to_string(), "Inventory[0].tag.display: missing key");
+}
+
+#[test]
+fn test_compound_preserves_file_order() {
+    use crate::{CompressionFormat, NbtFile};
+
+    let keys = ["zeta", "alpha", "Mid", "beta", "0", "omega"];
+    let mut map = CompoundMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.to_string(), NbtTag::Int(i as i32));
+    }
+    let original = NbtFile::new(NbtTag::Compound(map), "root".to_string(), CompressionFormat::None)
+        .write()
+        .unwrap();
+
+    let file = NbtFile::read(&original, None).unwrap();
+    let read_keys: Vec<&str> = file.root.as_compound().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(read_keys, keys);
+    assert_eq!(file.write().unwrap(), original);
+
+    let snbt = crate::format_snbt(&parse_snbt("{b:1,a:2,c:3}").unwrap());
+    assert_eq!(snbt, "{b:1,a:2,c:3}");
+}
+
+#[test]
+fn test_nbt_path_parse_and_get() {
+    use crate::{NbtPath, PathNode};
+
+    let root = parse_snbt(
+        r#"{Inventory:[{Slot:0b,id:"minecraft:stone",Count:3b},{Slot:1b,id:"minecraft:dirt",Count:1b,tag:{display:{Name:"Dirt"}}}],"weird key":{a:[I;4,5,6]}}"#,
+    )
+    .unwrap();
+
+    let path = NbtPath::parse(r#"Inventory[{id:"minecraft:dirt"}].tag.display.Name"#).unwrap();
+    assert_eq!(path.get(&root).unwrap().as_string(), "Dirt");
+    assert_eq!(path.to_string(), r#"Inventory[{id:"minecraft:dirt"}].tag.display.Name"#);
+
+    let all_ids = NbtPath::parse("Inventory[].id").unwrap();
+    let ids: Vec<String> = all_ids.get_all(&root).iter().map(|t| t.as_string().to_string()).collect();
+    assert_eq!(ids, ["minecraft:stone", "minecraft:dirt"]);
+
+    assert_eq!(NbtPath::parse("Inventory[-1].Slot").unwrap().get(&root).unwrap().into_owned(), NbtTag::Byte(1));
+    assert_eq!(NbtPath::parse(r#""weird key".a[1]"#).unwrap().get(&root).unwrap().into_owned(), NbtTag::Int(5));
+    assert_eq!(NbtPath::parse("{Inventory:[{Slot:1b}]}.Inventory[0].Count").unwrap().count(&root), 1);
+    assert_eq!(NbtPath::parse("Inventory[1].tag{display:{Name:\"Dirt\"}}").unwrap().count(&root), 1);
+    assert_eq!(NbtPath::parse("Inventory[5]").unwrap().count(&root), 0);
+
+    let nodes = NbtPath::parse("a.b[0][]").unwrap();
+    assert_eq!(
+        nodes.nodes(),
+        &[PathNode::Child("a".into()), PathNode::Child("b".into()), PathNode::Index(0), PathNode::All]
+    );
+    assert!(NbtPath::parse("").is_err());
+    assert!(NbtPath::parse("a.").is_err());
+    assert!(NbtPath::parse("a[x]").is_err());
+    assert!(NbtPath::parse("a.{b:1}").is_err());
+}
+
+#[test]
+fn test_nbt_path_modify() {
+    use crate::NbtPath;
+
+    let mut root = parse_snbt(r#"{Items:[{id:"a",Count:1b},{id:"b",Count:2b}],Data:[I;1,2,3]}"#).unwrap();
+    let path = |s: &str| NbtPath::parse(s).unwrap();
+
+    // set creates missing parents
+    assert_eq!(path("display.Lore").set(&mut root, NbtTag::string("x")), 1);
+    assert_eq!(root.get("display").unwrap().get_string("Lore"), "x");
+    assert_eq!(path("display.Lore").set(&mut root, NbtTag::string("x")), 0);
+    assert_eq!(path("Items[].Count").set(&mut root, NbtTag::Byte(5)), 2);
+    assert_eq!(path("Items[{id:\"b\"}].Count").get(&root).unwrap().into_owned(), NbtTag::Byte(5));
+    assert_eq!(path("Data[0]").set(&mut root, NbtTag::Int(9)), 1);
+    assert_eq!(root.get("Data"), Some(&NbtTag::IntArray(vec![9, 2, 3])));
+
+    // lists keep their element type
+    assert_eq!(path("Items[0]").set(&mut root, NbtTag::Int(1)), 0);
+
+    // insert / append / prepend
+    assert_eq!(path("Tags").append(&mut root, NbtTag::string("last")).unwrap(), 1);
+    assert_eq!(path("Tags").prepend(&mut root, NbtTag::string("first")).unwrap(), 1);
+    assert_eq!(path("Tags").insert(&mut root, 1, &[NbtTag::string("mid")]).unwrap(), 1);
+    let tags: Vec<String> = path("Tags[]").get_all(&root).iter().map(|t| t.as_string().to_string()).collect();
+    assert_eq!(tags, ["first", "mid", "last"]);
+    assert!(path("Tags").insert(&mut root, 10, &[NbtTag::string("x")]).is_err());
+    assert!(path("display").append(&mut root, NbtTag::Int(1)).is_err());
+    assert_eq!(path("Data").append(&mut root, NbtTag::Byte(4)).unwrap(), 1);
+    assert_eq!(root.get("Data"), Some(&NbtTag::IntArray(vec![9, 2, 3, 4])));
+
+    // merge
+    let source = parse_snbt("{display:{Name:\"n\"},Unbreakable:1b}").unwrap();
+    let source = source.as_compound().unwrap();
+    assert_eq!(path("Items[{id:\"a\"}].tag").merge(&mut root, source).unwrap(), 1);
+    assert_eq!(path("Items[0].tag.display.Name").get(&root).unwrap().as_string(), "n");
+    assert_eq!(path("Items[0].tag").merge(&mut root, source).unwrap(), 0);
+
+    // remove
+    assert_eq!(path("Items[{id:\"b\"}]").remove(&mut root), 1);
+    assert_eq!(path("Items[]").count(&root), 1);
+    assert_eq!(path("Tags[]").remove(&mut root), 3);
+    assert_eq!(root.get("Tags").unwrap().as_list().unwrap().0, &0);
+    assert_eq!(path("display").remove(&mut root), 1);
+    assert!(root.get("display").is_none());
+}
+
+#[test]
+fn test_reader_extract_paths() {
+    use crate::NbtPath;
+
+    let root = parse_snbt(
+        r#"{DataVersion:3700,Level:{Status:"full",xPos:1},block_entities:[{id:"minecraft:chest",Items:[{Slot:0b,id:"minecraft:stone"}]},{id:"minecraft:furnace"}],Heightmap:[L;1L,2L,3L]}"#,
+    )
+    .unwrap();
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer.write_u8(10);
+    writer.write_string("");
+    writer.write_tag(&root).unwrap();
+    let bytes = writer.into_bytes();
+
+    let mut reader = NbtReader::new(&bytes, Endian::Big);
+    assert_eq!(reader.find_path("Level.Status").unwrap(), Some(NbtTag::string("full")));
+    assert_eq!(reader.find_path("DataVersion").unwrap(), Some(NbtTag::Int(3700)));
+    assert_eq!(reader.find_path("Level.Missing").unwrap(), None);
+    assert_eq!(reader.cursor, 0);
+
+    let paths: Vec<NbtPath> = [
+        "block_entities[].id",
+        "block_entities[{id:\"minecraft:chest\"}].Items[0].id",
+        "block_entities[-1]",
+        "Heightmap[1]",
+        "{DataVersion:3700}.Level.xPos",
+    ]
+    .iter()
+    .map(|p| NbtPath::parse(p).unwrap())
+    .collect();
+    let found = reader.extract_paths(&paths).unwrap();
+    for (path, values) in paths.iter().zip(&found) {
+        let expected: Vec<NbtTag> = path.get_all(&root).into_iter().map(|t| t.into_owned()).collect();
+        assert_eq!(values, &expected, "{path}");
+    }
+    assert_eq!(found[0], vec![NbtTag::string("minecraft:chest"), NbtTag::string("minecraft:furnace")]);
+    assert_eq!(found[1], vec![NbtTag::string("minecraft:stone")]);
+    assert_eq!(found[3], vec![NbtTag::Long(2)]);
+    assert_eq!(found[4], vec![NbtTag::Int(1)]);
+}
+
+#[test]
+fn test_reader_extract_paths_rejects_bogus_list_header() {
+    use crate::NbtPath;
+
+    // {l: <list of End, length 0x7fffffff>} with nothing after the header
+    let bytes = [10, 0, 0, 9, 0, 1, b'l', 0, 0x7f, 0xff, 0xff, 0xff, 0];
+    let paths = [NbtPath::parse("l[]").unwrap(), NbtPath::parse("other").unwrap()];
+    let mut reader = NbtReader::new(&bytes, Endian::Big);
+    assert!(reader.extract_paths(&paths[..1]).is_err());
+    assert!(reader.extract_paths(&paths[1..]).is_err());
+}
+
+#[test]
+fn test_event_reader_rebuilds_tree() {
+    use crate::{NbtEvent, NbtEventReader};
+
+    fn build(events: &mut NbtEventReader, first: NbtEvent) -> NbtTag {
+        match first {
+            NbtEvent::StartCompound => {
+                let mut map = CompoundMap::new();
+                loop {
+                    match events.next_event().unwrap().unwrap() {
+                        NbtEvent::End => break,
+                        NbtEvent::Key(key) => {
+                            let value = events.next_event().unwrap().unwrap();
+                            map.insert(key.to_string(), build(events, value));
+                        }
+                        other => panic!("unexpected {other:?}"),
+                    }
+                }
+                NbtTag::Compound(map)
+            }
+            NbtEvent::StartList { element_type, len } => {
+                let mut items = Vec::with_capacity(len);
+                loop {
+                    match events.next_event().unwrap().unwrap() {
+                        NbtEvent::End => break,
+                        event => items.push(build(events, event)),
+                    }
+                }
+                NbtTag::List { tag_type: element_type, items }
+            }
+            NbtEvent::Byte(v) => NbtTag::Byte(v),
+            NbtEvent::Short(v) => NbtTag::Short(v),
+            NbtEvent::Int(v) => NbtTag::Int(v),
+            NbtEvent::Long(v) => NbtTag::Long(v),
+            NbtEvent::Float(v) => NbtTag::Float(v),
+            NbtEvent::Double(v) => NbtTag::Double(v),
+            NbtEvent::String(v) => NbtTag::string(v),
+            NbtEvent::ByteArray(v) => NbtTag::ByteArray(v.iter().map(|b| *b as i8).collect()),
+            NbtEvent::IntArray(v) => NbtTag::IntArray(v.to_vec()),
+            NbtEvent::LongArray(v) => NbtTag::LongArray(v.to_vec()),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    let root = parse_snbt(
+        r#"{a:1b,b:2s,c:3,d:4L,e:5.5f,f:6.5d,g:[B;1b,-2b],h:"text",i:[[1,2],[3]],j:{k:[I;7,8]},l:[L;9L],m:[]}"#,
+    )
+    .unwrap();
+    for endian in [Endian::Big, Endian::Little] {
+        let mut writer = NbtWriter::new(endian);
+        writer.write_u8(10);
+        writer.write_string("root");
+        writer.write_tag(&root).unwrap();
+        let bytes = writer.into_bytes();
+
+        let mut events = NbtEventReader::new(&bytes, endian);
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::Key("root")));
+        let first = events.next_event().unwrap().unwrap();
+        assert_eq!(build(&mut events, first), root);
+        assert_eq!(events.depth(), 0);
+        assert_eq!(events.next_event().unwrap(), None);
+        assert_eq!(events.position(), bytes.len());
+    }
+}
+
+#[test]
+fn test_event_reader_skip_and_count() {
+    use crate::{NbtEvent, NbtEventReader};
+
+    let root = parse_snbt(
+        r#"{Big:{x:[L;1L,2L,3L],y:"skip me"},Inventory:[{id:"a",Count:2b},{id:"b",Count:5b}],After:1}"#,
+    )
+    .unwrap();
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer.write_u8(10);
+    writer.write_string("");
+    writer.write_tag(&root).unwrap();
+    let bytes = writer.into_bytes();
+
+    // Sum item counts, skipping every other subtree
+    let mut events = NbtEventReader::new(&bytes, Endian::Big);
+    let mut total = 0;
+    let mut keys = Vec::new();
+    while let Some(event) = events.next_event().unwrap() {
+        match event {
+            NbtEvent::Key(_) if events.depth() == 0 => {}
+            NbtEvent::Key(key) if events.depth() == 1 => {
+                keys.push(key);
+                if key != "Inventory" {
+                    events.skip_value().unwrap();
+                }
+            }
+            NbtEvent::Key("Count") => {
+                if let Some(NbtEvent::Byte(count)) = events.next_event().unwrap() {
+                    total += count as i32;
+                }
+            }
+            NbtEvent::Key(_) => events.skip_value().unwrap(),
+            _ => {}
+        }
+    }
+    assert_eq!(total, 7);
+    keys.sort_unstable();
+    assert_eq!(keys, ["After", "Big", "Inventory"]);
+
+    // Walking keys in file order needs a known layout
+    {
+        let mut events = NbtEventReader::new(&bytes, Endian::Big);
+        events.next_event().unwrap();
+        events.next_event().unwrap();
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::Key("Big")));
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::StartCompound));
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::Key("x")));
+        match events.next_event().unwrap() {
+            Some(NbtEvent::LongArray(array)) => {
+                assert_eq!(array.len(), 3);
+                assert_eq!(array.get(2), Some(3));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        events.skip_container().unwrap();
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::Key("Inventory")));
+        assert!(events.skip_value().is_ok());
+        assert_eq!(events.next_event().unwrap(), Some(NbtEvent::Key("After")));
+    }
+}
+
+#[test]
+fn test_stream_writer_matches_tree_writer() {
+    use crate::{CompressedWriter, CompressionFormat, NbtFile, NbtStreamWriter};
+
+    let root = parse_snbt(
+        r#"{name:"export",count:3,pos:[1.0d,2.0d],blocks:[{id:"a",data:[I;1,2]},{id:"b",data:[I;]}],heights:[L;5L],raw:[B;1b],nested:{s:7s,f:1.5f}}"#,
+    )
+    .unwrap();
+    let expected = {
+        let mut tree = NbtWriter::new(Endian::Big);
+        tree.write_u8(10);
+        tree.write_string("root");
+        tree.write_tag(&root).unwrap();
+        tree.into_bytes()
+    };
+
+    let mut writer = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    writer.begin_root("root").unwrap();
+    writer.write_field("name", &NbtTag::string("export")).unwrap();
+    writer.key("count").unwrap();
+    writer.write_int(3).unwrap();
+    writer.key("pos").unwrap();
+    writer.begin_list(6, 2).unwrap();
+    writer.write_double(1.0).unwrap();
+    writer.write_double(2.0).unwrap();
+    writer.end().unwrap();
+    writer.key("blocks").unwrap();
+    writer.begin_list(10, 2).unwrap();
+    for (id, data) in [("a", vec![1, 2]), ("b", vec![])] {
+        writer.begin_compound().unwrap();
+        writer.key("id").unwrap();
+        writer.write_string(id).unwrap();
+        writer.key("data").unwrap();
+        writer.write_int_array(&data).unwrap();
+        writer.end().unwrap();
+    }
+    writer.end().unwrap();
+    writer.key("heights").unwrap();
+    writer.write_long_array(&[5]).unwrap();
+    writer.key("raw").unwrap();
+    writer.write_byte_array(&[1]).unwrap();
+    writer.write_field("nested", root.get("nested").unwrap()).unwrap();
+    writer.end().unwrap();
+    let written = writer.finish().unwrap();
+    assert_eq!(NbtFile::read(&written, None).unwrap().root, root);
+    // Byte-identical when the tree writer keeps the same key order
+    assert_eq!(written, expected);
+    assert_eq!(NbtFile::new(root.clone(), "root".to_string(), CompressionFormat::None).write().unwrap(), expected);
+
+    // Gzip stream reads back as the same tree
+    let mut writer = NbtStreamWriter::new(CompressedWriter::new(Vec::new(), CompressionFormat::Gzip), Endian::Big);
+    writer.key("root").unwrap();
+    writer.write_tag(&root).unwrap();
+    let compressed = writer.finish().unwrap().finish().unwrap();
+    let file = NbtFile::read(&compressed, None).unwrap();
+    assert_eq!(file.compression, CompressionFormat::Gzip);
+    assert_eq!(file.root, root);
+
+    // Arrays spanning several write chunks match the tree writer in both byte orders
+    let arrays = NbtTag::builder()
+        .with("bytes", (0..5000).map(|i| i as i8).collect::<Vec<i8>>())
+        .with("ints", (0..3000).map(|i| i * -65537).collect::<Vec<i32>>())
+        .with("longs", (0..1500).map(|i| i << 40).collect::<Vec<i64>>())
+        .build();
+    for endian in [Endian::Big, Endian::Little] {
+        let mut tree = NbtWriter::new(endian);
+        tree.write_u8(10);
+        tree.write_string("");
+        tree.write_tag(&arrays).unwrap();
+        let mut writer = NbtStreamWriter::new(Vec::new(), endian);
+        writer.key("").unwrap();
+        writer.write_tag(&arrays).unwrap();
+        assert_eq!(writer.finish().unwrap(), tree.into_bytes());
+    }
+}
+
+#[test]
+fn test_stream_writer_rejects_invalid_sequences() {
+    use crate::{NbtError, NbtStreamWriter};
+
+    let mut writer = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    assert!(writer.write_int(1).is_err()); // root without name
+    let mut writer = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    writer.begin_root("").unwrap();
+    assert!(writer.write_int(1).is_err()); // entry without key
+    writer.key("list").unwrap();
+    writer.begin_list(3, 1).unwrap();
+    assert!(matches!(writer.write_string("x"), Err(NbtError::ListTypeMismatch { expected: 3, found: 8 })));
+    assert!(writer.key("k").is_err());
+    assert!(writer.end().is_err()); // one element missing
+
+    let mut writer = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    writer.begin_root("").unwrap();
+    writer.key("list").unwrap();
+    writer.begin_list(3, 1).unwrap();
+    writer.write_int(1).unwrap();
+    assert!(writer.write_int(2).is_err());
+    writer.end().unwrap();
+    let mut unfinished = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    unfinished.begin_root("").unwrap();
+    assert!(unfinished.finish().is_err());
+    writer.end().unwrap();
+    assert!(writer.finish().is_ok());
+
+    // Invalid tags are rejected before anything is written
+    let mut writer = NbtStreamWriter::new(Vec::new(), Endian::Big);
+    let bad = NbtTag::List { tag_type: 3, items: vec![NbtTag::Int(1), NbtTag::string("x")] };
+    writer.key("root").unwrap();
+    assert!(matches!(writer.write_tag(&bad), Err(NbtError::ListTypeMismatch { expected: 3, found: 8 })));
+    assert!(writer.get_ref().is_empty());
+
+    // `{a: End}` would read back as an empty compound
+    let end_entry = NbtTag::builder().with("a", NbtTag::End).build();
+    assert!(matches!(writer.write_tag(&end_entry), Err(NbtError::InvalidTagType(0))));
+    assert!(writer.get_ref().is_empty());
+    let mut tree_writer = NbtWriter::new(Endian::Big);
+    assert!(matches!(tree_writer.write_tag(&end_entry), Err(NbtError::InvalidTagType(0))));
+    assert!(tree_writer.into_bytes().is_empty());
+}
+
+#[test]
+fn test_nbt_file_io_roundtrip() {
+    use crate::{CompressionFormat, NbtFile};
+    use std::io::Read;
+
+    // Reader returning one byte per call, to exercise magic-byte peeking
+    struct Trickle<'a>(&'a [u8]);
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    let root = parse_snbt(r#"{Data:{LevelName:"world",SpawnX:10},list:[1,2,3]}"#).unwrap();
+    let dir = std::env::temp_dir();
+    for format in [CompressionFormat::None, CompressionFormat::Gzip, CompressionFormat::Zlib] {
+        let file = NbtFile::new(root.clone(), "".to_string(), format);
+        let bytes = file.write().unwrap();
+
+        let from_reader = NbtFile::from_reader(Trickle(&bytes)).unwrap();
+        assert_eq!(from_reader.compression, format);
+        assert_eq!(from_reader.root, root);
+
+        let path = dir.join(format!("nbt_io_test_{}_{:?}.dat", std::process::id(), format));
+        file.save(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+        let loaded = NbtFile::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.compression, format);
+        assert_eq!(loaded.root_name, "");
+        assert_eq!(loaded.root, root);
+    }
+
+    assert!(NbtFile::from_reader(&[][..]).is_err());
+    assert!(NbtFile::from_path(dir.join("nbt_io_test_missing.dat")).is_err());
+
+    // Files are streamed to the writer instead of being serialized up front
+    struct Chunks(Vec<usize>);
+    impl std::io::Write for Chunks {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.push(buf.len());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let big = NbtTag::Compound((0..20_000).map(|i| (format!("key{i}"), NbtTag::Long(i))).collect());
+    let mut chunks = Chunks(Vec::new());
+    NbtFile::new(big, String::new(), CompressionFormat::None).to_writer(&mut chunks).unwrap();
+    assert!(chunks.0.iter().sum::<usize>() > 200_000);
+    assert!(chunks.0.iter().all(|&len| len <= 8192));
+}
+
+#[test]
+fn test_tag_ref_borrows_and_converts() {
+    use crate::NbtTagRef;
+
+    let root = parse_snbt(
+        r#"{name:"Steve",pos:[1.5d,64.0d,-2.0d],inv:[{id:"stone",n:3b},{id:"dirt",n:1b}],heights:[L;1L,2L],bytes:[B;1b,-1b]}"#,
+    )
+    .unwrap();
+    for endian in [Endian::Big, Endian::Little] {
+        let mut writer = NbtWriter::new(endian);
+        writer.write_u8(10);
+        writer.write_string("root");
+        writer.write_tag(&root).unwrap();
+        let bytes = writer.into_bytes();
+
+        let (name, tag) = NbtTagRef::parse(&bytes, endian).unwrap();
+        assert_eq!(name.to_str(), Some("root"));
+        assert_eq!(tag.get("name").unwrap().as_str(), Some("Steve"));
+        let pos = tag.get("pos").unwrap().as_list().unwrap();
+        assert_eq!(pos.len(), 3);
+        assert_eq!(pos.get(2).unwrap().as_number(), -2.0);
+        let inv = tag.get("inv").unwrap().as_list().unwrap();
+        assert_eq!(inv.get(1).unwrap().get("id").unwrap().as_str(), Some("dirt"));
+        match tag.get("heights").unwrap() {
+            NbtTagRef::LongArray(array) => assert_eq!(array.to_vec(), vec![1, 2]),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(tag.get("missing").is_none());
+        assert_eq!(tag.to_tag(), root);
+        assert_eq!(NbtTag::from(&tag), root);
+
+        // Truncated input is rejected up front
+        assert!(NbtTagRef::parse(&bytes[..bytes.len() - 1], endian).is_err());
+    }
+}
+
+#[test]
+fn test_tag_ref_decodes_modified_utf8() {
+    use crate::NbtTagRef;
+
+    // "a\0b" with NUL as C0 80, then U+1F600 as a surrogate pair
+    let raw: &[u8] = &[b'a', 0xC0, 0x80, b'b', 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+    let mut bytes = vec![8, 0, 0, 0, raw.len() as u8];
+    bytes.extend_from_slice(raw);
+    let (_, tag) = NbtTagRef::parse(&bytes, Endian::Big).unwrap();
+    match &tag {
+        NbtTagRef::String(s) => {
+            assert_eq!(s.as_bytes(), raw);
+            assert_eq!(s.to_str(), None);
+            assert_eq!(s.decode(), "a\0b\u{1F600}");
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(tag.to_tag(), NbtTag::String("a\0b\u{1F600}".to_string()));
+}
+
+#[test]
+fn test_reader_limits_reject_malicious_input() {
+    use crate::{compress_data, CompressionFormat, NbtError, NbtEventReader, NbtFile, NbtLimits};
+
+    // 1000 nested lists of lists: {"":[[[...]]]}
+    let mut deep = vec![10, 0, 0, 9, 0, 0];
+    for _ in 0..1000 {
+        deep.extend_from_slice(&[9, 0, 0, 0, 1]);
+    }
+    deep.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    assert!(matches!(
+        NbtFile::read(&deep, None),
+        Err(NbtError::DepthLimitExceeded { limit: 512 })
+    ));
+    let mut reader = NbtReader::new(&deep[3..], Endian::Big);
+    assert!(matches!(reader.skip_tag(10), Err(NbtError::DepthLimitExceeded { .. })));
+    assert!(matches!(
+        crate::NbtTagRef::parse(&deep, Endian::Big),
+        Err(NbtError::DepthLimitExceeded { .. })
+    ));
+    let events: Result<Vec<_>, _> = NbtEventReader::new(&deep, Endian::Big).collect();
+    assert!(matches!(events, Err(NbtError::DepthLimitExceeded { .. })));
+
+    // Negative and oversized lengths fail before allocating
+    let negative = [10, 0, 0, 7, 0, 1, b'a', 0xFF, 0xFF, 0xFF, 0xFF, 0];
+    assert!(matches!(NbtFile::read(&negative, None), Err(NbtError::NegativeLength(-1))));
+    let huge = [10, 0, 0, 12, 0, 1, b'a', 0x7F, 0xFF, 0xFF, 0xFF, 0];
+    assert!(matches!(NbtFile::read(&huge, None), Err(NbtError::UnexpectedEof)));
+
+    // Allocation quota (vanilla network limit) and decompressed size
+    let root = NbtTag::Compound(CompoundMap::from_iter([(
+        "data".to_string(),
+        NbtTag::LongArray(vec![0; 512 * 1024]),
+    )]));
+    let bytes = NbtFile::new(root.clone(), String::new(), CompressionFormat::None).write().unwrap();
+    let limits = NbtLimits { max_allocation: 1024 * 1024, ..NbtLimits::unlimited() };
+    assert!(matches!(
+        NbtFile::read_with_limits(&bytes, limits),
+        Err(NbtError::AllocationLimitExceeded { limit: 1048576 })
+    ));
+    assert!(NbtFile::read_with_limits(&bytes, NbtLimits::network()).is_err());
+    let limits = NbtLimits { max_decompressed_size: 1024, ..NbtLimits::unlimited() };
+    let gzip = compress_data(&bytes, CompressionFormat::Gzip).unwrap();
+    assert!(matches!(
+        NbtFile::read_with_limits(&gzip, limits),
+        Err(NbtError::DecompressedSizeExceeded { limit: 1024 })
+    ));
+    assert!(matches!(
+        NbtFile::from_reader_with_limits(&gzip[..], limits),
+        Err(NbtError::DecompressedSizeExceeded { .. })
+    ));
+    assert_eq!(NbtFile::read_with_limits(&gzip, NbtLimits::untrusted()).unwrap().root, root);
+
+    // Plain readers only check the nesting depth
+    assert_eq!(NbtReader::new(&bytes, Endian::Big).limits(), &NbtLimits::unlimited());
+    assert_eq!(NbtLimits::default(), NbtLimits::unlimited());
+}
+
+#[test]
+fn test_bulk_array_decoding() {
+    let root = NbtTag::Compound(CompoundMap::from_iter([
+        ("bytes".to_string(), NbtTag::ByteArray(vec![i8::MIN, -1, 0, 1, i8::MAX])),
+        ("ints".to_string(), NbtTag::IntArray(vec![i32::MIN, -2, 0, 0x0102_0304, i32::MAX])),
+        ("longs".to_string(), NbtTag::LongArray(vec![i64::MIN, -3, 0x0102_0304_0506_0708, i64::MAX])),
+        ("empty".to_string(), NbtTag::LongArray(vec![])),
+    ]));
+    for endian in [Endian::Big, Endian::Little] {
+        let mut writer = NbtWriter::new(endian);
+        writer.write_tag(&root).unwrap();
+        let bytes = writer.into_bytes();
+        assert_eq!(NbtReader::new(&bytes, endian).read_tag(10).unwrap(), root);
+
+        // A length running past the data fails without decoding
+        let truncated = &bytes[..bytes.len() - 5];
+        assert!(NbtReader::new(truncated, endian).read_tag(10).is_err());
+    }
+}
+
+#[test]
+fn test_typed_list_mutation() {
+    use crate::NbtError;
+
+    let mut list = NbtTag::list_of(vec![]).unwrap();
+    assert_eq!(list, NbtTag::List { tag_type: 0, items: vec![] });
+    list.push(NbtTag::Int(1)).unwrap();
+    list.push(NbtTag::Int(3)).unwrap();
+    list.insert_at(1, NbtTag::Int(2)).unwrap();
+    assert!(matches!(
+        list.push(NbtTag::String("x".into())),
+        Err(NbtError::ListTypeMismatch { expected: 3, found: 8 })
+    ));
+    assert!(matches!(list.set_at(0, NbtTag::Byte(0)), Err(NbtError::ListTypeMismatch { .. })));
+    assert!(matches!(list.insert_at(9, NbtTag::Int(0)), Err(NbtError::IndexOutOfBounds { .. })));
+    assert!(list.push(NbtTag::End).is_err());
+    assert_eq!(list.set_at(0, NbtTag::Int(10)).unwrap(), NbtTag::Int(1));
+    assert_eq!(list, parse_snbt("[10, 2, 3]").unwrap());
+
+    // Emptied lists go back to End and then accept any type
+    for _ in 0..3 {
+        list.remove_at(0).unwrap();
+    }
+    assert_eq!(list, NbtTag::List { tag_type: 0, items: vec![] });
+    list.push(NbtTag::String("a".into())).unwrap();
+    assert_eq!(list.as_list().unwrap().0, &8);
+
+    assert!(NbtTag::list_of(vec![NbtTag::Int(1), NbtTag::Long(2)]).is_err());
+    assert!(NbtTag::Int(0).push(NbtTag::Int(1)).is_err());
+}
+
+#[test]
+fn test_writer_rejects_mismatched_lists() {
+    use crate::NbtError;
+
+    let bad = NbtTag::Compound(CompoundMap::from_iter([(
+        "list".to_string(),
+        NbtTag::List { tag_type: 3, items: vec![NbtTag::Int(1), NbtTag::String("x".into())] },
+    )]));
+    let mut writer = NbtWriter::new(Endian::Big);
+    writer.write_u8(10);
+    assert!(matches!(writer.write_tag(&bad), Err(NbtError::ListTypeMismatch { expected: 3, found: 8 })));
+    // Nothing of the rejected tag reaches the buffer
+    assert_eq!(writer.into_bytes(), vec![10]);
+
+    let end_items = NbtTag::List { tag_type: 0, items: vec![NbtTag::End] };
+    assert!(NbtWriter::new(Endian::Big).write_tag(&end_items).is_err());
+    let file = crate::NbtFile::new(bad, String::new(), crate::CompressionFormat::Gzip);
+    assert!(file.write().is_err());
+}
+
+#[test]
+fn test_checked_accessors() {
+    let mut root = parse_snbt(
+        r#"{Data:{LevelName:"world",Time:100L,hardcore:1b},Pos:[1.0d,2.0d,"x"],Items:[{id:"stone"},{id:"dirt"}],ids:[I;1,2]}"#,
+    )
+    .unwrap();
+
+    let data = root.try_get("Data").unwrap();
+    assert_eq!(data.try_get_str("LevelName").unwrap(), "world");
+    assert_eq!(data.try_get_i64("Time").unwrap(), 100);
+    assert!(data.try_get_bool("hardcore").unwrap());
+    assert_eq!(root.try_get_int_array("ids").unwrap(), &[1, 2]);
+    assert_eq!(root.try_get_list("Items").unwrap().len(), 2);
+    let items = root.try_get_list_of::<CompoundMap>("Items").unwrap();
+    assert_eq!(items[1]["id"], NbtTag::String("dirt".into()));
+
+    assert_eq!(data.try_get_i32("Time").unwrap_err().to_string(), "Time: expected Int, found Long");
+    assert_eq!(data.try_get_str("levelname").unwrap_err().to_string(), "levelname: missing key");
+    assert_eq!(
+        root.try_get_list_of::<f64>("Pos").unwrap_err().to_string(),
+        "Pos[2]: expected Double, found String"
+    );
+    assert!(root.try_get_i32("Data").is_err());
+    assert!(NbtTag::Int(1).try_get("x").is_err());
+
+    *root.try_get_mut("Data").unwrap().try_get_i64_mut("Time").unwrap() += 1;
+    root.get_mut("Data").unwrap().try_get_string_mut("LevelName").unwrap().push('2');
+    root.try_get_list_mut("Items").unwrap().push(NbtTag::compound()).unwrap();
+    assert!(root.try_get_list_mut("Items").unwrap().push(NbtTag::Int(1)).is_err());
+    assert_eq!(root.try_get("Data").unwrap().try_get_i64("Time").unwrap(), 101);
+    assert_eq!(root.get("Data").unwrap().try_get_str("LevelName").unwrap(), "world2");
+    assert_eq!(root.try_get_list("Items").unwrap().len(), 3);
+}
+
+#[test]
+fn test_builder_and_conversions() {
+    let tag = NbtTag::builder()
+        .with("id", "minecraft:pig")
+        .with("Health", 10.0f32)
+        .with("OnGround", true)
+        .with_optional("CustomName", None::<String>)
+        .with_list("Pos", [1.0, 64.0, 2.0])
+        .with_list("Tags", Vec::<String>::new())
+        .with_compound("Owner", |owner| owner.with("Name", "Alex"))
+        .with("Motion", vec![0.0f64, 0.5])
+        .with("ids", vec![1i32, 2, 3])
+        .build();
+
+    assert_eq!(tag.get_string("id"), "minecraft:pig");
+    assert_eq!(tag.get("Health"), Some(&NbtTag::Float(10.0)));
+    assert_eq!(tag.get("OnGround"), Some(&NbtTag::Byte(1)));
+    assert!(tag.get("CustomName").is_none());
+    assert_eq!(tag.get("Pos").unwrap().type_id(), 9);
+    assert_eq!(*tag.get("Pos").unwrap().as_list().unwrap().0, 6);
+    assert_eq!(*tag.get("Tags").unwrap().as_list().unwrap().0, 0);
+    assert_eq!(tag.get("Owner").unwrap().get_string("Name"), "Alex");
+    assert_eq!(*tag.get("Motion").unwrap().as_list().unwrap().0, 6);
+    assert_eq!(tag.get("ids"), Some(&NbtTag::IntArray(vec![1, 2, 3])));
+
+    let mut scores = std::collections::HashMap::new();
+    scores.insert("alex".to_string(), 3);
+    assert_eq!(NbtTag::from(scores).get("alex"), Some(&NbtTag::Int(3)));
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_nbt_macro() {
+    let health = 20.0f32;
+    let name = "Steve";
+    let tag = crate::nbt! {
+        "Pos": [1.0d, 64.0d, 2.0d],
+        "id": "minecraft:pig",
+        Health: health,
+        Count: 1b,
+        Time: -5L,
+        Owner: { Name: name },
+        Names: [name, "Alex"],
+        Blocks: [B; 1, 2, -3],
+        Longs: [L; 1, 2L],
+        Empty: [],
+    };
+
+    let expected = parse_snbt(
+        r#"{Pos:[1.0d,64.0d,2.0d],id:"minecraft:pig",Health:20.0f,Count:1b,Time:-5L,Owner:{Name:"Steve"},Names:["Steve","Alex"],Blocks:[B;1b,2b,-3b],Longs:[L;1L,2L],Empty:[]}"#,
+    )
+    .unwrap();
+    assert_eq!(tag, expected);
+    assert_eq!(crate::nbt!(42s), NbtTag::Short(42));
+}
+
+#[test]
+fn test_structural_diff() {
+    let old = parse_snbt(
+        r#"{id:"minecraft:pig",Health:10.0f,Age:1,Tags:["a","b","c"],Items:[{id:"stone",Count:1b},{id:"dirt",Count:2b}],Motion:[0.0d,0.0d]}"#,
+    )
+    .unwrap();
+    let new = parse_snbt(
+        r#"{Tags:["x","a","c","d"],id:"minecraft:pig",Health:8.0f,Age:"old",Items:[{id:"stone",Count:5b},{id:"dirt",Count:2b}],Motion:[],Name:"Bob"}"#,
+    )
+    .unwrap();
+
+    // Compound entries come out in key order
+    let diff = crate::diff(&old, &new);
+    let rendered: Vec<String> = diff.iter().map(|change| change.to_string()).collect();
+    let expected = vec![
+        "~ Age: 1 -> old (Int -> String)",
+        "~ Health: 10f -> 8f",
+        "~ Items[0].Count: 1b -> 5b",
+        "- Motion[0]: 0d",
+        "- Motion[0]: 0d",
+        "+ Name: Bob",
+        "+ Tags[0]: x",
+        "- Tags[2]: b",
+        "+ Tags[3]: d",
+    ];
+    assert_eq!(rendered, expected);
+    assert!(old.diff(&old).is_empty());
+
+    let mut patched = old.clone();
+    patched.apply_diff(&diff).unwrap();
+    assert_eq!(patched, new);
+
+    // Patches apply to other trees of the same shape
+    let mut other = parse_snbt(r#"{id:"minecraft:cow",Health:3.0f,Age:1,Tags:["a","b","c"],Items:[{id:"stone",Count:1b}],Motion:[1.0d,2.0d]}"#).unwrap();
+    diff.apply(&mut other).unwrap();
+    assert_eq!(other.get("Health"), Some(&NbtTag::Float(8.0)));
+    assert_eq!(other.get_string("id"), "minecraft:cow");
+
+    // A failed patch leaves the target unchanged
+    let mut missing = parse_snbt(r#"{Health:1.0f}"#).unwrap();
+    assert!(diff.apply(&mut missing).is_err());
+    assert_eq!(missing, parse_snbt(r#"{Health:1.0f}"#).unwrap());
+
+    // Element types survive lists going empty or filled
+    let typed_empty = |tag_type| NbtTag::List { tag_type, items: Vec::new() };
+    let old = NbtTag::builder()
+        .with("emptied", NbtTag::list_of(vec![NbtTag::Int(1)]).unwrap())
+        .with("filled", typed_empty(10))
+        .build();
+    let new = NbtTag::builder()
+        .with("emptied", typed_empty(3))
+        .with("filled", NbtTag::list_of(vec![NbtTag::Long(1)]).unwrap())
+        .build();
+    let mut patched = old.clone();
+    patched.apply_diff(&old.diff(&new)).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn test_merge_strategies() {
+    use crate::ListMerge;
+
+    let base = parse_snbt(
+        r#"{display:{Name:"Kit",Lore:["a"]},Items:[{Slot:0b,id:"stone",Count:1b},{Slot:1b,id:"dirt",Count:1b}],Tags:["x"],Level:1}"#,
+    )
+    .unwrap();
+    let patch = parse_snbt(
+        r#"{display:{Lore:["b"]},Items:[{Slot:1b,Count:5b},{Slot:2b,id:"sand",Count:1b}],Tags:[],Level:"max"}"#,
+    )
+    .unwrap();
+
+    // Vanilla: compounds merged recursively, lists and other values replaced
+    let mut merged = base.clone();
+    assert!(merged.merge(&patch));
+    assert_eq!(merged.get("display").unwrap().get_string("Name"), "Kit");
+    assert_eq!(merged.get("display").unwrap().get("Lore"), patch.get("display").unwrap().get("Lore"));
+    assert_eq!(merged.get("Items"), patch.get("Items"));
+    assert_eq!(merged.get("Level"), Some(&NbtTag::String("max".to_string())));
+    assert!(!merged.merge(&patch));
+
+    let mut appended = base.clone();
+    appended.merge_with(&patch, &ListMerge::Append);
+    assert_eq!(
+        appended.get("display").unwrap().get("Lore"),
+        Some(&parse_snbt(r#"["a","b"]"#).unwrap())
+    );
+    assert_eq!(appended.get("Items").unwrap().as_list().unwrap().1.len(), 4);
+    assert_eq!(appended.get("Tags"), base.get("Tags"));
+
+    let mut by_slot = base.clone();
+    by_slot.merge_with(&patch, &ListMerge::ByKey("Slot".to_string()));
+    assert_eq!(
+        by_slot.get("Items"),
+        Some(&parse_snbt(r#"[{Slot:0b,id:"stone",Count:1b},{Slot:1b,id:"dirt",Count:5b},{Slot:2b,id:"sand",Count:1b}]"#).unwrap())
+    );
+    assert_eq!(by_slot.get("display").unwrap().get("Lore"), patch.get("display").unwrap().get("Lore"));
+
+    // Non-compound sources replace the target
+    let mut scalar = NbtTag::Int(1);
+    assert!(scalar.merge(&NbtTag::Int(2)));
+    assert_eq!(scalar, NbtTag::Int(2));
+}
+
+#[test]
+fn test_tree_walks() {
+    use crate::{NbtPath, Visitor};
+
+    let mut tag = parse_snbt(
+        r#"{Items:[{id:"minecraft:grass",Count:1b,tag:{Damage:3}},{id:"minecraft:stone",Count:2b}],Name:"chest",ids:[I;1,-2,3]}"#,
+    )
+    .unwrap();
+
+    struct Strings(Vec<String>);
+    impl Visitor for Strings {
+        fn visit(&mut self, path: &NbtPath, tag: &NbtTag) -> bool {
+            if let NbtTag::String(value) = tag {
+                self.0.push(format!("{path}={value}"));
+            }
+            true
+        }
+    }
+    let mut strings = Strings(Vec::new());
+    tag.walk(&mut strings);
+    strings.0.sort_unstable();
+    assert_eq!(
+        strings.0,
+        vec!["Items[0].id=minecraft:grass", "Items[1].id=minecraft:stone", "Name=chest"]
+    );
+
+    // Returning false skips the children
+    let mut visited = 0;
+    tag.walk(&mut |path: &NbtPath, _: &NbtTag| {
+        visited += 1;
+        path.to_string() != "Items"
+    });
+    assert_eq!(visited, 7);
+
+    tag.map_strings(|path, value| {
+        (path.to_string().ends_with(".id") && value == "minecraft:grass")
+            .then(|| "minecraft:short_grass".to_string())
+    });
+    assert_eq!(tag.get_path(&"Items[0].id".parse().unwrap()).unwrap().as_string(), "minecraft:short_grass");
+
+    tag.walk_mut(&mut |_: &NbtPath, tag: &mut NbtTag| {
+        if let NbtTag::Int(v) = tag {
+            *v = v.abs();
+        }
+        true
+    })
+    .unwrap();
+    assert_eq!(tag.get("ids"), Some(&NbtTag::IntArray(vec![1, 2, 3])));
+
+    // Lists take their elements' new type, End once emptied; mixed types are an error
+    let mut lists = parse_snbt("{a:[1,2],b:[1,2],c:[1,2]}").unwrap();
+    lists
+        .walk_mut(&mut |path: &NbtPath, tag: &mut NbtTag| {
+            match (path.to_string().as_str(), &mut *tag) {
+                ("a", NbtTag::List { items, .. }) => items.clear(),
+                (p, NbtTag::Int(v)) if p.starts_with('b') => *tag = NbtTag::Long(*v as i64),
+                _ => {}
+            }
+            true
+        })
+        .unwrap();
+    assert_eq!(lists.get("a").unwrap().as_list().unwrap().0, &0);
+    assert_eq!(lists.get("b").unwrap().as_list().unwrap().0, &4);
+    let error = lists
+        .walk_mut(&mut |path: &NbtPath, tag: &mut NbtTag| {
+            if path.to_string() == "c[1]" {
+                *tag = NbtTag::string("x");
+            }
+            true
+        })
+        .unwrap_err();
+    assert_eq!(error.to_string(), "c[1]: expected Int, found String");
+
+    let found = tag.find_all(|_, tag| tag.is_number());
+    let mut paths: Vec<String> = found.iter().map(|(path, _)| path.to_string()).collect();
+    paths.sort_unstable();
+    assert_eq!(paths, vec!["Items[0].Count", "Items[0].tag.Damage", "Items[1].Count", "ids[0]", "ids[1]", "ids[2]"]);
+
+    tag.retain(|path, tag| {
+        !(path.to_string().ends_with(".tag") || tag == &NbtTag::Int(2) || tag.get_string("id") == "minecraft:stone")
+    });
+    assert_eq!(
+        tag,
+        parse_snbt(r#"{Items:[{id:"minecraft:short_grass",Count:1b}],Name:"chest",ids:[I;1,3]}"#).unwrap()
+    );
+}
+
+#[test]
+fn test_canonical_form() {
+    use crate::CanonicalTag;
+    use std::collections::HashSet;
+
+    let a = parse_snbt(r#"{b:[1.0f,2.0f],a:{y:1b,x:"s"},n:1.0d,e:[]}"#).unwrap();
+    let b = parse_snbt(r#"{a:{x:"s",y:1b},e:[],n:1.0d,b:[1.0f,2.0f]}"#).unwrap();
+    assert_eq!(a.canonical_bytes(), b.canonical_bytes());
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.fingerprint128(), b.fingerprint128());
+    assert_ne!(a.fingerprint(), NbtTag::Int(1).fingerprint());
+
+    // Stable across runs: FNV-1a of [3, 0, 0, 0, 1]
+    assert_eq!(NbtTag::Int(1).fingerprint(), 0xf16b3403a27b5565);
+
+    // NaN payloads are normalised, empty list element types ignored
+    let nan = CanonicalTag(NbtTag::Double(f64::from_bits(0x7ff8_0000_0000_0001)));
+    assert_eq!(nan, CanonicalTag(NbtTag::Double(f64::NAN)));
+    assert_eq!(CanonicalTag(NbtTag::list(8)), CanonicalTag(NbtTag::list(0)));
+    assert_ne!(CanonicalTag(NbtTag::Float(0.0)), CanonicalTag(NbtTag::Float(-0.0)));
+
+    let palette: HashSet<CanonicalTag> = [a.clone(), b, NbtTag::Int(1), NbtTag::Int(1)]
+        .into_iter()
+        .map(CanonicalTag)
+        .collect();
+    assert_eq!(palette.len(), 2);
+
+    let mut sorted = vec![
+        CanonicalTag(NbtTag::String("b".into())),
+        CanonicalTag(NbtTag::Int(5)),
+        CanonicalTag(NbtTag::String("a".into())),
+        CanonicalTag(NbtTag::Byte(9)),
+    ];
+    sorted.sort();
+    let sorted: Vec<NbtTag> = sorted.into_iter().map(CanonicalTag::into_inner).collect();
+    assert_eq!(
+        sorted,
+        vec![NbtTag::Byte(9), NbtTag::Int(5), NbtTag::String("a".into()), NbtTag::String("b".into())]
+    );
+}
+
+#[test]
+fn test_partial_matching() {
+    let entity = parse_snbt(
+        r#"{id:"minecraft:zombie",Health:20.0f,Tags:["boss","undead"],Inventory:[{Slot:0b,id:"stone",Count:1b},{Slot:1b,id:"dirt",Count:3b}],Empty:[]}"#,
+    )
+    .unwrap();
+    let pattern = |snbt: &str| parse_snbt(snbt).unwrap();
+
+    assert!(entity.matches(&pattern("{}"), true));
+    assert!(entity.matches(&pattern(r#"{id:"minecraft:zombie"}"#), true));
+    assert!(!entity.matches(&pattern(r#"{id:"minecraft:zombie",Health:20.0d}"#), true));
+    assert!(!entity.matches(&pattern(r#"{Missing:1b}"#), true));
+
+    // Every pattern element must match some element, in any order
+    assert!(entity.matches(&pattern(r#"{Tags:["undead"]}"#), true));
+    assert!(entity.matches(&pattern(r#"{Tags:["undead","boss"]}"#), true));
+    assert!(entity.matches(&pattern(r#"{Inventory:[{id:"dirt"}]}"#), true));
+    assert!(!entity.matches(&pattern(r#"{Inventory:[{id:"sand"}]}"#), true));
+    assert!(entity.matches(&pattern(r#"{Empty:[]}"#), true));
+    assert!(!entity.matches(&pattern(r#"{Tags:[]}"#), true));
+
+    // Without partial lists, lists must be equal
+    assert!(!entity.matches(&pattern(r#"{Tags:["undead"]}"#), false));
+    assert!(entity.matches(&pattern(r#"{Tags:["boss","undead"]}"#), false));
+    assert!(!entity.matches(&pattern(r#"{Inventory:[{id:"stone"},{id:"dirt"}]}"#), false));
+
+    assert!(NbtTag::Int(3).matches(&NbtTag::Int(3), false));
+    assert!(!NbtTag::Int(3).matches(&NbtTag::Long(3), true));
+}
+
+#[test]
+fn test_level_data_editor() {
+    use crate::{CompressionFormat, GameRuleValue, LevelData, NbtFile, WorldBorder};
+
+    let root = parse_snbt(
+        r#"{Data:{LevelName:"world",DataVersion:3700,Version:{Id:3700,Name:"1.20.4"},GameType:0,Difficulty:2b,hardcore:0b,SpawnX:10,SpawnY:64,SpawnZ:-5,BorderSize:1000.0d,GameRules:{keepInventory:"false",randomTickSpeed:"3",modRule:"7"},DataPacks:{Enabled:["vanilla","file/extra"],Disabled:[]},WorldGenSettings:{seed:42L,dimensions:{}},CustomModData:{x:1b}}}"#,
+    )
+    .unwrap();
+    let bytes = NbtFile::new(root, "".to_string(), CompressionFormat::Gzip).write().unwrap();
+
+    let mut level = LevelData::read(&bytes).unwrap();
+    assert_eq!(level.level_name().unwrap(), "world");
+    assert_eq!(level.version_name().unwrap(), "1.20.4");
+    assert_eq!(level.spawn().unwrap(), (10, 64, -5));
+    assert_eq!(level.seed().unwrap(), 42);
+    assert_eq!(level.difficulty().unwrap(), 2);
+    assert!(!level.hardcore().unwrap());
+    assert_eq!(level.world_border().unwrap(), WorldBorder { size: 1000.0, ..WorldBorder::default() });
+    assert_eq!(level.game_rule_value("randomTickSpeed"), Some(GameRuleValue::Int(3)));
+    assert_eq!(level.time().unwrap_err().to_string(), "Data.Time: missing key");
+
+    // Game rule values are checked against vanilla types and existing values
+    level.set_game_rule("keepInventory", true).unwrap();
+    assert_eq!(level.game_rule("keepInventory"), Some("true"));
+    assert!(level.set_game_rule("keepInventory", 1).is_err());
+    assert!(level.set_game_rule("randomTickSpeed", false).is_err());
+    assert!(level.set_game_rule("modRule", true).is_err());
+    level.set_game_rule("newModRule", 5).unwrap();
+
+    assert!(level.disable_data_pack("file/extra").unwrap());
+    assert!(!level.disable_data_pack("file/extra").unwrap());
+    assert_eq!(level.enabled_data_packs().unwrap(), vec!["vanilla"]);
+    assert_eq!(level.disabled_data_packs().unwrap(), vec!["file/extra"]);
+
+    level.set_spawn(0, 70, 0);
+    level.set_data_version(3800);
+    level.set_level_name("renamed");
+    level.set_world_border(&WorldBorder { center_x: 50.0, ..WorldBorder::default() });
+
+    let written = level.write().unwrap();
+    let file = NbtFile::read(&written, None).unwrap();
+    assert_eq!(file.compression, CompressionFormat::Gzip);
+    assert_eq!(file.root_name, "");
+    let reread = LevelData::from_file(file).unwrap();
+    assert_eq!(reread.spawn().unwrap(), (0, 70, 0));
+    assert_eq!(reread.data().get("Version").unwrap().get("Id"), Some(&NbtTag::Int(3800)));
+    assert_eq!(reread.world_border().unwrap().center_x, 50.0);
+    assert_eq!(reread.data().get("CustomModData"), Some(&parse_snbt("{x:1b}").unwrap()));
+    assert_eq!(reread.game_rule("newModRule"), Some("5"));
+
+    assert!(LevelData::from_file(NbtFile::new(NbtTag::compound(), String::new(), CompressionFormat::Gzip)).is_err());
+}
+
+#[test]
+fn test_player_data_editor() {
+    use crate::{CompressionFormat, InventorySlot, NbtFile, PlayerData};
+
+    let root = parse_snbt(
+        concat!(
+            r#"{DataVersion:3700,UUID:[I;-132296786,2112623056,-1486552928,-920753162],Pos:[1.5d,64.0d,-2.5d],Motion:[0.1d,-0.5d,0.0d],Rotation:[90.0f,10.0f],FallDistance:3.0f,Dimension:"minecraft:overworld",RootVehicle:{Attach:[I;1,2,3,4]},Health:20.0f,"#,
+            r#"Inventory:[{Slot:0b,id:"minecraft:stone",Count:64b},{Slot:10b,id:"minecraft:dirt",Count:1b},{Slot:103b,id:"minecraft:diamond_helmet",Count:1b},{Slot:-106b,id:"minecraft:shield",Count:1b}],"#,
+            r#"EnderItems:[{Slot:26b,id:"minecraft:emerald",Count:3b}],"#,
+            r#"attributes:[{id:"minecraft:generic.max_health",base:20.0d}],abilities:{mayfly:0b,flySpeed:0.05f},CustomMod:{x:1b}}"#,
+        ),
+    )
+    .unwrap();
+    let bytes = NbtFile::new(root, "".to_string(), CompressionFormat::Gzip).write().unwrap();
+    let mut player = PlayerData::read(&bytes).unwrap();
+
+    assert_eq!(player.uuid_string().unwrap(), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+    assert_eq!(player.position().unwrap(), (1.5, 64.0, -2.5));
+    assert_eq!(player.rotation().unwrap(), (90.0, 10.0));
+    assert_eq!(player.attribute_base("generic.max_health"), Some(20.0));
+    assert!(player.set_attribute_base("minecraft:generic.max_health", 40.0));
+    assert_eq!(player.attribute_base("minecraft:generic.max_health"), Some(40.0));
+
+    let get_id = |player: &PlayerData, slot| player.item(slot).unwrap().map(|item| item["id"].as_string().to_string());
+    assert_eq!(get_id(&player, InventorySlot::Hotbar(0)).as_deref(), Some("minecraft:stone"));
+    assert_eq!(get_id(&player, InventorySlot::Main(1)).as_deref(), Some("minecraft:dirt"));
+    assert_eq!(get_id(&player, InventorySlot::Head).as_deref(), Some("minecraft:diamond_helmet"));
+    assert_eq!(get_id(&player, InventorySlot::Offhand).as_deref(), Some("minecraft:shield"));
+    assert_eq!(get_id(&player, InventorySlot::EnderChest(26)).as_deref(), Some("minecraft:emerald"));
+    assert!(player.item(InventorySlot::Hotbar(9)).is_err());
+    assert_eq!(player.items().len(), 5);
+
+    let sword = parse_snbt(r#"{id:"minecraft:diamond_sword",Count:1b}"#).unwrap().as_compound().unwrap().clone();
+    let previous = player.set_item(InventorySlot::Hotbar(0), sword).unwrap();
+    assert_eq!(previous.unwrap()["id"].as_string(), "minecraft:stone");
+    assert_eq!(player.item(InventorySlot::Hotbar(0)).unwrap().unwrap()["Slot"], NbtTag::Byte(0));
+    assert!(player.remove_item(InventorySlot::EnderChest(26)).unwrap().is_some());
+    assert_eq!(player.root().get("EnderItems"), Some(&NbtTag::list(0)));
+
+    player.teleport(100.0, 70.0, 100.0, "minecraft:the_nether");
+    assert_eq!(player.dimension().unwrap(), "minecraft:the_nether");
+    assert_eq!(player.position().unwrap(), (100.0, 70.0, 100.0));
+    assert_eq!(player.root().get("FallDistance"), Some(&NbtTag::Float(0.0)));
+    assert!(player.root().get("RootVehicle").is_none());
+    assert!(player.root().get("fall_distance").is_none());
+
+    let reread = PlayerData::read(&player.write().unwrap()).unwrap();
+    assert_eq!(reread.file().compression, CompressionFormat::Gzip);
+    assert_eq!(reread.root().get("CustomMod"), Some(&parse_snbt("{x:1b}").unwrap()));
+    assert_eq!(reread.uuid().unwrap(), 0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6);
+
+    // Newer files keep armor and offhand in `equipment`
+    let mut modern = PlayerData::from_file(NbtFile::new(
+        parse_snbt(r#"{DataVersion:4325,Inventory:[]}"#).unwrap(),
+        String::new(),
+        CompressionFormat::Gzip,
+    ))
+    .unwrap();
+    let boots = parse_snbt(r#"{id:"minecraft:iron_boots",count:1}"#).unwrap().as_compound().unwrap().clone();
+    modern.set_item(InventorySlot::Feet, boots).unwrap();
+    assert_eq!(modern.root().get("equipment").unwrap().get("feet").unwrap().get_string("id"), "minecraft:iron_boots");
+    assert_eq!(modern.items().len(), 1);
+    assert!(modern.remove_item(InventorySlot::Feet).unwrap().is_some());
+}
+
+#[test]
+fn test_item_stack_formats() {
+    use crate::{FromNbt, ItemFormat, ItemStack, NbtError};
+
+    let legacy = parse_snbt(concat!(
+        r#"{id:"minecraft:diamond_sword",Count:1b,tag:{Damage:5,"#,
+        r#"display:{Name:'{"text":"Blade"}',Lore:['"one"','"two"'],color:3},"#,
+        r#"Enchantments:[{id:"minecraft:sharpness",lvl:3s}],Unbreakable:1b,owner:"steve"}}"#
+    ))
+    .unwrap();
+    let mut item = ItemStack::from_nbt(&legacy).unwrap();
+    assert_eq!(item.format(), ItemFormat::Legacy);
+    assert_eq!(item.id(), "minecraft:diamond_sword");
+    assert_eq!(item.count(), 1);
+    assert_eq!(item.damage(), 5);
+    assert_eq!(item.name(), Some(&NbtTag::from(r#"{"text":"Blade"}"#)));
+    assert_eq!(item.lore().len(), 2);
+    assert_eq!(item.enchantment_level("sharpness"), Some(3));
+    assert_eq!(item.custom_data().get("owner"), Some(&NbtTag::from("steve")));
+
+    let modern = item.to_components();
+    assert_eq!(modern.format(), ItemFormat::Components);
+    let components = modern.as_compound()["components"].as_compound().unwrap();
+    assert_eq!(modern.as_compound()["count"], NbtTag::Int(1));
+    assert_eq!(components["minecraft:damage"], NbtTag::Int(5));
+    assert_eq!(components["minecraft:enchantments"].get("levels").unwrap().get("minecraft:sharpness").unwrap(), &NbtTag::Int(3));
+    assert_eq!(components["minecraft:unbreakable"], NbtTag::compound());
+    assert_eq!(components["minecraft:custom_data"], parse_snbt(r#"{display:{color:3},owner:"steve"}"#).unwrap());
+    assert_eq!(modern.name(), item.name());
+    assert_eq!(modern.enchantments(), item.enchantments());
+
+    // Back to the legacy layout
+    let back = modern.to_legacy().unwrap();
+    assert_eq!(back.as_compound()["Count"], NbtTag::Byte(1));
+    assert_eq!(back.as_compound()["tag"].canonical_bytes(), legacy.get("tag").unwrap().canonical_bytes());
+
+    item.set_enchantment("minecraft:sharpness", 5);
+    item.set_enchantment("minecraft:looting", 2);
+    assert!(item.remove_enchantment("minecraft:sharpness"));
+    assert!(!item.remove_enchantment("minecraft:sharpness"));
+    assert_eq!(item.enchantments(), vec![("minecraft:looting".to_string(), 2)]);
+
+    let mut stack = ItemStack::new("minecraft:apple", 64);
+    stack.set_enchantment("minecraft:unbreaking", 1);
+    stack.set_custom_data("quest", 7);
+    stack.set_damage(2);
+    assert_eq!(stack.count(), 64);
+    assert_eq!(stack.custom_data().get("quest"), Some(&NbtTag::Int(7)));
+    assert_eq!(stack.to_legacy().unwrap().enchantment_level("unbreaking"), Some(1));
+
+    stack.set_count(200).unwrap();
+    assert!(matches!(stack.to_legacy(), Err(NbtError::InvalidNumber(_))));
+    stack.set_count(1).unwrap();
+    let mut food = stack.into_compound();
+    food.get_mut("components").unwrap().as_compound_mut().unwrap().insert("minecraft:food".to_string(), NbtTag::compound());
+    assert!(matches!(ItemStack::from_compound(food).unwrap().to_legacy(), Err(NbtError::UnsupportedComponent(_))));
+    assert!(ItemStack::from_nbt(&parse_snbt("{Count:1b}").unwrap()).is_err());
+
+    // Legacy counts must fit the Count byte
+    let mut old = ItemStack::from_nbt(&legacy).unwrap();
+    assert!(matches!(old.set_count(200), Err(NbtError::InvalidNumber(_))));
+    assert_eq!(old.count(), 1);
+
+    // Custom data is the same in both layouts; display names and `Unbreakable:0b` are not part of it
+    let plain = parse_snbt(r#"{id:"minecraft:stick",Count:1b,tag:{display:{Name:'"x"'},Unbreakable:0b,owner:"s"}}"#).unwrap();
+    let plain = ItemStack::from_nbt(&plain).unwrap();
+    assert_eq!(plain.custom_data(), plain.to_components().custom_data());
+    assert_eq!(NbtTag::Compound(plain.custom_data()), parse_snbt(r#"{owner:"s"}"#).unwrap());
+    let item = ItemStack::from_nbt(&legacy).unwrap();
+    assert_eq!(item.custom_data(), item.to_components().custom_data());
+}
+
+#[test]
+fn test_uuid_forms() {
+    use crate::{format_uuid, parse_uuid, uuid_from_longs, uuid_to_ints, uuid_to_longs, NbtPath};
+
+    let uuid = 0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6u128;
+    let (most, least) = uuid_to_longs(uuid);
+    assert_eq!(uuid_from_longs(most, least), uuid);
+    assert_eq!(uuid_to_ints(uuid), [-132296786, 2112623056, -1486552928, -920753162]);
+    assert_eq!(format_uuid(uuid), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+    assert_eq!(parse_uuid("F81D4FAE7DEC11D0A76500A0C91E6BF6").unwrap(), uuid);
+    assert_eq!(parse_uuid("1-2-3-4-5").unwrap(), 0x00000001_0002_0003_0004_000000000005);
+    assert!(parse_uuid("f81d4fae-7dec-11d0-a765").is_err());
+    assert!(parse_uuid("+81d4fae-7dec-11d0-a765-00a0c91e6bf6").is_err());
+
+    assert_eq!(NbtTag::from_uuid(uuid).as_uuid(), Some(uuid));
+    assert_eq!(NbtTag::from("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").as_uuid(), Some(uuid));
+    assert_eq!(NbtTag::IntArray(vec![1, 2, 3]).as_uuid(), None);
+
+    let mut entity = NbtTag::builder()
+        .with("UUIDMost", most)
+        .with("UUIDLeast", least)
+        .with("Owner", format_uuid(uuid))
+        .build();
+    assert_eq!(entity.try_get_uuid("UUID").unwrap(), uuid);
+    assert_eq!(entity.try_get_uuid("Owner").unwrap(), uuid);
+    assert!(entity.try_get_uuid("Target").is_err());
+    assert!(entity.set_uuid("UUID", 1));
+    assert!(entity.get("UUIDMost").is_none());
+    assert_eq!(entity.get("UUID"), Some(&NbtTag::IntArray(vec![0, 0, 0, 1])));
+
+    // Pairs are migrated in place at any depth
+    let mut tree = NbtTag::builder()
+        .with("Name", "wolf")
+        .with("OwnerUUIDMost", most)
+        .with("OwnerUUIDLeast", least)
+        .with("Health", 8.0f32)
+        .with("Attributes", NbtTag::list_of(vec![NbtTag::builder().with("UUIDMost", 1i64).with("UUIDLeast", 2i64).build()]).unwrap())
+        .with("KeepMost", 3i64)
+        .build();
+    let changed = tree.migrate_uuids();
+    assert_eq!(changed, vec![NbtPath::new(Vec::new()), NbtPath::parse("Attributes[0]").unwrap()]);
+    let keys: Vec<&String> = tree.as_compound().unwrap().keys().collect();
+    assert_eq!(keys, ["Name", "Owner", "Health", "Attributes", "KeepMost"]);
+    assert_eq!(tree.try_get_uuid("Owner").unwrap(), uuid);
+    assert_eq!(tree.get("Attributes").unwrap().as_list().unwrap().1[0].get("UUID"), Some(&NbtTag::IntArray(vec![0, 1, 0, 2])));
+    assert!(tree.migrate_uuids().is_empty());
+
+    // Without renames pairs keep their name
+    let mut projectile = NbtTag::builder().with("OwnerUUIDMost", most).with("OwnerUUIDLeast", least).build();
+    assert_eq!(projectile.migrate_uuids_with(&[]).len(), 1);
+    assert_eq!(projectile.try_get_uuid("OwnerUUID").unwrap(), uuid);
+
+    // Two pairs renamed onto `Owner` are both left alone, other pairs still migrate
+    let mut clash = NbtTag::builder()
+        .with("OwnerUUIDMost", most)
+        .with("OwnerUUIDLeast", least)
+        .with("OwnerMost", 1i64)
+        .with("OwnerLeast", 2i64)
+        .with("UUIDMost", most)
+        .with("UUIDLeast", least)
+        .build();
+    assert_eq!(clash.migrate_uuids().len(), 1);
+    assert!(clash.get("Owner").is_none());
+    assert_eq!(clash.get("OwnerMost"), Some(&NbtTag::Long(1)));
+    assert_eq!(clash.get("OwnerUUIDLeast"), Some(&NbtTag::Long(least)));
+    assert_eq!(clash.try_get_uuid("UUID").unwrap(), uuid);
+    assert!(clash.get("UUIDMost").is_none());
+}
@@ -0,0 +1,270 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Type};
+
+mod literal;
+
+/// Derive `nbt::NbtCompound`, `nbt::FromNbt` and `nbt::ToNbt` for a struct with named fields.
+///
+/// Container attribute: `#[nbt(rename_all = "PascalCase" | "camelCase" | "snake_case" | "SCREAMING_SNAKE_CASE")]`.
+///
+/// Field attributes:
+/// - `rename = "Key"`: NBT key for the field
+/// - `default` / `default = "path::to::fn"`: value used when the key is missing
+/// - `flatten`: read and write the field's own entries in the same compound
+/// - `skip`: never read nor written (`Default::default()` on read)
+/// - `byte_array` / `int_array` / `long_array`: write a `Vec` as a typed array instead of a list
+///
+/// `Option<T>` fields are optional: a missing key reads as `None` and `None` is not written.
+#[proc_macro_derive(NbtCompound, attributes(nbt))]
+pub fn derive_nbt_compound(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Build an `nbt::NbtTag` from SNBT-like syntax.
+///
+/// ```ignore
+/// let pig = nbt!{ "Pos": [1.0d, 64.0d, 2.0d], id: "minecraft:pig", Health: 10.0f, Tags: [health] };
+/// ```
+///
+/// - Keys are string literals or identifiers; a body without braces is a compound.
+/// - Numbers take SNBT suffixes (`b`, `s`, `L`, `f`, `d`) or Rust ones (`i16`, `f32`, ...);
+///   unsuffixed integers are `Int` and unsuffixed decimals `Double`. `0b` does not lex as a
+///   byte, write `0i8` or `false`.
+/// - `[B; ...]`, `[I; ...]` and `[L; ...]` are typed arrays.
+/// - Any other expression is converted with `NbtTag::from`.
+///
+/// Literal lists with mixed types and out-of-range numbers are compile errors. Lists holding
+/// expressions are checked when built and panic if the element types differ.
+#[proc_macro]
+pub fn nbt(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as literal::Root).expand().into()
+}
+
+#[derive(Clone, Copy)]
+enum ArrayKind {
+    Byte,
+    Int,
+    Long,
+}
+
+enum DefaultValue {
+    Trait,
+    Function(syn::ExprPath),
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    default: Option<DefaultValue>,
+    flatten: bool,
+    skip: bool,
+    array: Option<ArrayKind>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "NbtCompound can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "NbtCompound can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut rename_all = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("nbt")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let value: LitStr = meta.value()?.parse()?;
+                match value.value().as_str() {
+                    "PascalCase" | "camelCase" | "snake_case" | "SCREAMING_SNAKE_CASE" => {
+                        rename_all = Some(value.value());
+                        Ok(())
+                    }
+                    _ => Err(syn::Error::new_spanned(value, "unknown rename_all case")),
+                }
+            } else {
+                Err(meta.error("unknown nbt container attribute"))
+            }
+        })?;
+    }
+
+    let mut reads = Vec::new();
+    let mut writes = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let attrs = parse_field_attrs(field)?;
+        let raw_name = ident.to_string().trim_start_matches("r#").to_string();
+        let key = match (&attrs.rename, &rename_all) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(case)) => apply_case(&raw_name, case),
+            (None, None) => raw_name,
+        };
+        let ty = &field.ty;
+
+        if attrs.skip {
+            reads.push(quote! { #ident: ::std::default::Default::default() });
+            continue;
+        }
+
+        if attrs.flatten {
+            reads.push(quote! { #ident: <#ty as ::nbt::NbtCompound>::from_compound(map)? });
+            writes.push(quote! { ::nbt::NbtCompound::write_compound(&self.#ident, map); });
+            continue;
+        }
+
+        let optional = is_option(ty);
+        let read = match (&attrs.default, optional) {
+            (_, true) => quote! { ::nbt::read_optional_field(map, #key)? },
+            (Some(DefaultValue::Trait), false) => {
+                quote! { ::nbt::read_optional_field(map, #key)?.unwrap_or_default() }
+            }
+            (Some(DefaultValue::Function(path)), false) => {
+                quote! { ::nbt::read_optional_field(map, #key)?.unwrap_or_else(#path) }
+            }
+            (None, false) => quote! { ::nbt::read_field(map, #key)? },
+        };
+        reads.push(quote! { #ident: #read });
+
+        let convert = match attrs.array {
+            Some(ArrayKind::Byte) => {
+                quote! { ::nbt::NbtTag::ByteArray(::std::clone::Clone::clone(value)) }
+            }
+            Some(ArrayKind::Int) => {
+                quote! { ::nbt::NbtTag::IntArray(::std::clone::Clone::clone(value)) }
+            }
+            Some(ArrayKind::Long) => {
+                quote! { ::nbt::NbtTag::LongArray(::std::clone::Clone::clone(value)) }
+            }
+            None => quote! { ::nbt::ToNbt::to_nbt(value) },
+        };
+        writes.push(if optional {
+            quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    map.insert(::std::string::String::from(#key), #convert);
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let value = &self.#ident;
+                    map.insert(::std::string::String::from(#key), #convert);
+                }
+            }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::nbt::NbtCompound for #name #ty_generics #where_clause {
+            fn from_compound(
+                map: &::nbt::CompoundMap,
+            ) -> ::nbt::Result<Self> {
+                ::std::result::Result::Ok(Self { #(#reads,)* })
+            }
+
+            fn write_compound(
+                &self,
+                map: &mut ::nbt::CompoundMap,
+            ) {
+                #(#writes)*
+            }
+        }
+
+        impl #impl_generics ::nbt::FromNbt for #name #ty_generics #where_clause {
+            fn from_nbt(tag: &::nbt::NbtTag) -> ::nbt::Result<Self> {
+                ::nbt::compound_from_nbt(tag)
+            }
+        }
+
+        impl #impl_generics ::nbt::ToNbt for #name #ty_generics #where_clause {
+            fn to_nbt(&self) -> ::nbt::NbtTag {
+                ::nbt::NbtTag::Compound(::nbt::NbtCompound::to_compound(self))
+            }
+        }
+    })
+}
+
+fn parse_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("nbt")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: LitStr = meta.value()?.parse()?;
+                attrs.rename = Some(value.value());
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                    let value: LitStr = meta.value()?.parse()?;
+                    DefaultValue::Function(value.parse()?)
+                } else {
+                    DefaultValue::Trait
+                });
+            } else if meta.path.is_ident("flatten") {
+                attrs.flatten = true;
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else if meta.path.is_ident("byte_array") {
+                attrs.array = Some(ArrayKind::Byte);
+            } else if meta.path.is_ident("int_array") {
+                attrs.array = Some(ArrayKind::Int);
+            } else if meta.path.is_ident("long_array") {
+                attrs.array = Some(ArrayKind::Long);
+            } else {
+                return Err(meta.error("unknown nbt field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn apply_case(field: &str, case: &str) -> String {
+    let words = field.split('_').filter(|w| !w.is_empty());
+    match case {
+        "PascalCase" | "camelCase" => {
+            let mut out = String::new();
+            for (i, word) in words.enumerate() {
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    if i == 0 && case == "camelCase" {
+                        out.push(first);
+                    } else {
+                        out.extend(first.to_uppercase());
+                    }
+                    out.push_str(chars.as_str());
+                }
+            }
+            out
+        }
+        "SCREAMING_SNAKE_CASE" => field.to_uppercase(),
+        _ => field.to_string(),
+    }
+}
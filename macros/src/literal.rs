@@ -0,0 +1,350 @@
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, bracketed, Ident, LitBool, LitFloat, LitInt, LitStr, Token};
+
+/// Parsed `nbt!` input: a compound body (`"key": value, ...`) or a single value
+pub struct Root(Value);
+
+enum Value {
+    Compound(Vec<(String, Value)>),
+    List(Vec<Value>),
+    Array(u8, Vec<ArrayElement>),
+    /// Literal with a type known at compile time
+    Known(u8, TokenStream),
+    /// Rust expression converted with `NbtTag::from`
+    Expr(syn::Expr),
+}
+
+enum ArrayElement {
+    Literal(TokenStream),
+    Expr(syn::Expr),
+}
+
+const TYPE_NAMES: [&str; 13] = [
+    "End",
+    "Byte",
+    "Short",
+    "Int",
+    "Long",
+    "Float",
+    "Double",
+    "ByteArray",
+    "String",
+    "List",
+    "Compound",
+    "IntArray",
+    "LongArray",
+];
+
+impl Parse for Root {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let is_entries = input.is_empty()
+            || ((input.peek(LitStr) || input.peek(Ident))
+                && input.peek2(Token![:])
+                && !input.peek2(Token![::]));
+        let value = if is_entries {
+            parse_entries(input)?
+        } else {
+            let value = input.parse()?;
+            if !input.is_empty() {
+                return Err(input.error("unexpected tokens after the value"));
+            }
+            value
+        };
+        Ok(Root(value))
+    }
+}
+
+impl Root {
+    pub fn expand(&self) -> TokenStream {
+        self.0.expand()
+    }
+}
+
+fn parse_entries(input: ParseStream) -> syn::Result<Value> {
+    let mut entries: Vec<(String, Value)> = Vec::new();
+    while !input.is_empty() {
+        let (key, span) = if input.peek(LitStr) {
+            let key: LitStr = input.parse()?;
+            (key.value(), key.span())
+        } else {
+            let key: Ident = input.parse()?;
+            (key.to_string(), key.span())
+        };
+        if entries.iter().any(|(existing, _)| *existing == key) {
+            return Err(syn::Error::new(span, format!("duplicate key \"{key}\"")));
+        }
+        input.parse::<Token![:]>()?;
+        entries.push((key, input.parse()?));
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    Ok(Value::Compound(entries))
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            return parse_entries(&content);
+        }
+        if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            return parse_sequence(&content);
+        }
+        if input.peek(LitStr) {
+            let value: LitStr = input.parse()?;
+            return Ok(Value::Known(
+                8,
+                quote!(::nbt::NbtTag::String(::std::string::String::from(#value))),
+            ));
+        }
+        if input.peek(LitBool) {
+            let value: LitBool = input.parse()?;
+            let byte = value.value as i8;
+            return Ok(Value::Known(1, quote!(::nbt::NbtTag::Byte(#byte))));
+        }
+        if let Some(number) = parse_number(input)? {
+            return number_value(&number);
+        }
+        Ok(Value::Expr(input.parse()?))
+    }
+}
+
+fn parse_sequence(input: ParseStream) -> syn::Result<Value> {
+    let array_type = if input.peek(Ident) && input.peek2(Token![;]) {
+        let prefix: Ident = input.parse()?;
+        input.parse::<Token![;]>()?;
+        match prefix.to_string().as_str() {
+            "B" => Some(7),
+            "I" => Some(11),
+            "L" => Some(12),
+            _ => {
+                return Err(syn::Error::new(
+                    prefix.span(),
+                    "expected B, I or L array prefix",
+                ))
+            }
+        }
+    } else {
+        None
+    };
+
+    match array_type {
+        Some(array_type) => {
+            let element_type = match array_type {
+                7 => 1,
+                11 => 3,
+                _ => 4,
+            };
+            let mut elements = Vec::new();
+            while !input.is_empty() {
+                elements.push(match parse_number(input)? {
+                    // Unsuffixed integers take the array's element type
+                    Some(number) if number.tag_type == element_type || number.tag_type == 3 => {
+                        ArrayElement::Literal(typed_literal(&number, element_type)?)
+                    }
+                    Some(number) => {
+                        return Err(type_error(number.span, element_type, number.tag_type));
+                    }
+                    None => ArrayElement::Expr(input.parse()?),
+                });
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+            }
+            Ok(Value::Array(array_type, elements))
+        }
+        None => {
+            let mut items: Vec<Value> = Vec::new();
+            let mut list_type = None;
+            while !input.is_empty() {
+                let span = input.span();
+                let item: Value = input.parse()?;
+                if let Some(found) = item.static_type() {
+                    match list_type {
+                        None => list_type = Some(found),
+                        Some(expected) if expected != found => {
+                            return Err(type_error(span, expected, found));
+                        }
+                        _ => {}
+                    }
+                }
+                items.push(item);
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+            }
+            Ok(Value::List(items))
+        }
+    }
+}
+
+fn type_error(span: Span, expected: u8, found: u8) -> syn::Error {
+    syn::Error::new(
+        span,
+        format!(
+            "list elements must have the same type: expected {}, found {}",
+            TYPE_NAMES[expected as usize], TYPE_NAMES[found as usize]
+        ),
+    )
+}
+
+struct Number {
+    tag_type: u8,
+    text: String,
+    span: Span,
+}
+
+// Numbers with SNBT (`1b`, `2s`, `3L`, `1.5f`, `2.0d`) or Rust (`1i16`, `2f32`) suffixes
+fn parse_number(input: ParseStream) -> syn::Result<Option<Number>> {
+    let negative = input.peek(Token![-]) && (input.peek2(LitInt) || input.peek2(LitFloat));
+    if !negative && !input.peek(LitInt) && !input.peek(LitFloat) {
+        return Ok(None);
+    }
+    if negative {
+        input.parse::<Token![-]>()?;
+    }
+
+    let (digits, suffix, span, is_float) = if input.peek(LitInt) {
+        let lit: LitInt = input.parse()?;
+        (
+            lit.base10_digits().to_string(),
+            lit.suffix().to_string(),
+            lit.span(),
+            false,
+        )
+    } else {
+        let lit: LitFloat = input.parse()?;
+        (
+            lit.base10_digits().to_string(),
+            lit.suffix().to_string(),
+            lit.span(),
+            true,
+        )
+    };
+    let tag_type = match suffix.as_str() {
+        "b" | "B" | "i8" if !is_float => 1,
+        "s" | "S" | "i16" if !is_float => 2,
+        "" | "i32" if !is_float => 3,
+        "l" | "L" | "i64" if !is_float => 4,
+        "f" | "F" | "f32" => 5,
+        "" | "d" | "D" | "f64" => 6,
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                format!("unsupported suffix \"{suffix}\""),
+            ))
+        }
+    };
+    Ok(Some(Number {
+        tag_type,
+        text: format!("{}{digits}", if negative { "-" } else { "" }),
+        span,
+    }))
+}
+
+// Suffixed Rust literal of the given numeric tag type, checking its range
+fn typed_literal(number: &Number, tag_type: u8) -> syn::Result<TokenStream> {
+    let text = &number.text;
+    let out_of_range = || syn::Error::new(number.span, format!("{text} is out of range"));
+    let literal = match tag_type {
+        1 => Literal::i8_suffixed(text.parse().map_err(|_| out_of_range())?),
+        2 => Literal::i16_suffixed(text.parse().map_err(|_| out_of_range())?),
+        3 => Literal::i32_suffixed(text.parse().map_err(|_| out_of_range())?),
+        4 => Literal::i64_suffixed(text.parse().map_err(|_| out_of_range())?),
+        5 => {
+            let v: f32 = text.parse().map_err(|_| out_of_range())?;
+            if !v.is_finite() {
+                return Err(out_of_range());
+            }
+            Literal::f32_suffixed(v)
+        }
+        _ => {
+            let v: f64 = text.parse().map_err(|_| out_of_range())?;
+            if !v.is_finite() {
+                return Err(out_of_range());
+            }
+            Literal::f64_suffixed(v)
+        }
+    };
+    let mut literal = literal;
+    literal.set_span(number.span);
+    Ok(quote!(#literal))
+}
+
+fn number_value(number: &Number) -> syn::Result<Value> {
+    let literal = typed_literal(number, number.tag_type)?;
+    let variant = Ident::new(TYPE_NAMES[number.tag_type as usize], Span::call_site());
+    Ok(Value::Known(
+        number.tag_type,
+        quote_spanned!(number.span=> ::nbt::NbtTag::#variant(#literal)),
+    ))
+}
+
+impl Value {
+    fn static_type(&self) -> Option<u8> {
+        match self {
+            Value::Compound(_) => Some(10),
+            Value::List(_) => Some(9),
+            Value::Array(array_type, _) => Some(*array_type),
+            Value::Known(tag_type, _) => Some(*tag_type),
+            Value::Expr(_) => None,
+        }
+    }
+
+    fn expand(&self) -> TokenStream {
+        match self {
+            Value::Compound(entries) => {
+                let inserts = entries.iter().map(|(key, value)| {
+                    let value = value.expand();
+                    quote!(map.insert(::std::string::String::from(#key), #value);)
+                });
+                quote!({
+                    let mut map = ::nbt::CompoundMap::new();
+                    #(#inserts)*
+                    ::nbt::NbtTag::Compound(map)
+                })
+            }
+            Value::List(items) => {
+                let expanded = items.iter().map(Value::expand);
+                let known: Vec<u8> = items.iter().filter_map(Value::static_type).collect();
+                if known.len() == items.len() {
+                    let tag_type = known.first().copied().unwrap_or(0);
+                    quote!(::nbt::NbtTag::List {
+                        tag_type: #tag_type,
+                        items: ::std::vec![#(#expanded),*],
+                    })
+                } else {
+                    // Element types of interpolated expressions are only known at run time
+                    quote!(::nbt::NbtTag::list_of(::std::vec![#(#expanded),*])
+                        .expect("nbt!: list elements must have the same type"))
+                }
+            }
+            Value::Array(array_type, elements) => {
+                let (variant, ty) = match array_type {
+                    7 => (quote!(ByteArray), quote!(i8)),
+                    11 => (quote!(IntArray), quote!(i32)),
+                    _ => (quote!(LongArray), quote!(i64)),
+                };
+                let elements = elements.iter().map(|element| match element {
+                    ArrayElement::Literal(tokens) => tokens.clone(),
+                    ArrayElement::Expr(expr) => quote!({
+                        let value: #ty = #expr;
+                        value
+                    }),
+                });
+                quote!(::nbt::NbtTag::#variant(::std::vec![#(#elements),*]))
+            }
+            Value::Known(_, tokens) => tokens.clone(),
+            Value::Expr(expr) => quote!(::nbt::NbtTag::from(#expr)),
+        }
+    }
+}